@@ -0,0 +1,96 @@
+use crate::types::ByteArray;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use tiny_keccak::{Hasher, Keccak};
+
+/// Computes the keccak-256 hash of some data
+pub fn keccak256(data: &[u8]) -> ByteArray<32> {
+    let mut hasher = Keccak::v256();
+    let mut hash = [0_u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut hash);
+    ByteArray::<32>(hash)
+}
+
+/// Recovers the address which signed a message hash
+///
+/// Signatures with a high `s` value, which are valid before the Homestead hard fork, are
+/// normalized first. Returns `None` if the signature is invalid.
+pub fn recover_address(
+    message_hash: &ByteArray<32>,
+    y_parity: u8,
+    r: &ByteArray<32>,
+    s: &ByteArray<32>,
+) -> Option<ByteArray<20>> {
+    if y_parity > 1 {
+        return None;
+    }
+    let mut signature = Signature::from_scalars(r.0, s.0).ok()?;
+    let mut is_y_odd = y_parity == 1;
+    if let Some(normalized) = signature.normalize_s() {
+        signature = normalized;
+        is_y_odd = !is_y_odd;
+    }
+    let recovery_id = RecoveryId::new(is_y_odd, false);
+    let key = VerifyingKey::recover_from_prehash(&message_hash.0, &signature, recovery_id).ok()?;
+
+    // The address consists of the last 20 bytes of the hash of the uncompressed public key
+    let public_key = key.to_encoded_point(false);
+    let hash = keccak256(&public_key.as_bytes()[1..]);
+    let mut address = [0_u8; 20];
+    address.copy_from_slice(&hash.0[12..]);
+    Some(ByteArray::<20>(address))
+}
+
+/// Checks if `s` is at most half of the order of secp256k1, as required since Homestead
+pub fn is_low_s(s: &ByteArray<32>) -> bool {
+    Signature::from_scalars([1_u8; 32], s.0)
+        .map(|signature| signature.normalize_s().is_none())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            keccak256(&[]).to_string(),
+            "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn test_recover_address() {
+        // The signing hash and signature of the example transaction from EIP-155
+        let hash = ByteArray::<32>([
+            0xda, 0xf5, 0xa7, 0x79, 0xae, 0x97, 0x2f, 0x97, 0x21, 0x97, 0x30, 0x3d, 0x7b, 0x57,
+            0x47, 0x46, 0xc7, 0xef, 0x83, 0xea, 0xda, 0xc0, 0xf2, 0x79, 0x1a, 0xd2, 0x3d, 0xb9,
+            0x2e, 0x4c, 0x8e, 0x53,
+        ]);
+        let r = ByteArray::<32>([
+            0x28, 0xef, 0x61, 0x34, 0x0b, 0xd9, 0x39, 0xbc, 0x21, 0x95, 0xfe, 0x53, 0x75, 0x67,
+            0x86, 0x60, 0x03, 0xe1, 0xa1, 0x5d, 0x3c, 0x71, 0xff, 0x63, 0xe1, 0x59, 0x06, 0x20,
+            0xaa, 0x63, 0x62, 0x76,
+        ]);
+        let s = ByteArray::<32>([
+            0x67, 0xcb, 0xe9, 0xd8, 0x99, 0x7f, 0x76, 0x1a, 0xec, 0xb7, 0x03, 0x30, 0x4b, 0x38,
+            0x00, 0xcc, 0xf5, 0x55, 0xc9, 0xf3, 0xdc, 0x64, 0x21, 0x4b, 0x29, 0x7f, 0xb1, 0x96,
+            0x6a, 0x3b, 0x6d, 0x83,
+        ]);
+        assert!(is_low_s(&s));
+        assert_eq!(
+            recover_address(&hash, 0, &r, &s).unwrap().to_string(),
+            "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+        );
+        assert_ne!(
+            recover_address(&hash, 1, &r, &s),
+            recover_address(&hash, 0, &r, &s)
+        );
+        assert_eq!(recover_address(&hash, 2, &r, &s), None);
+        assert_eq!(
+            recover_address(&hash, 0, &ByteArray::<32>([0; 32]), &s),
+            None
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use crate::numeric::{u16_from_bytes_be, u32_from_bytes_be, NumericError};
 use crate::rlp::RlpDeserializer;
+use crate::table::{FreezerTable, TableInfo};
 use log::{debug, info, trace};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use snap::raw::Decoder;
-use std::collections::HashMap;
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
@@ -12,8 +13,12 @@ use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 // A single index consists of 2 bytes (u16) for the file number and 4 bytes (u32) for the offset
-const FILE_NUMBER_BYTE_SIZE: u64 = 2;
-const OFFSET_NUMBER_BYTE_SIZE: u64 = 4;
+pub(crate) const FILE_NUMBER_BYTE_SIZE: u64 = 2;
+pub(crate) const OFFSET_NUMBER_BYTE_SIZE: u64 = 4;
+pub(crate) const INDEX_ENTRY_SIZE: u64 = FILE_NUMBER_BYTE_SIZE + OFFSET_NUMBER_BYTE_SIZE;
+
+// Since geth v1.13 the chain freezer is located in a subfolder of the ancient folder
+const CHAIN_FOLDER: &str = "chain";
 
 /// Allows to export block parts from the `chaindata/ancient` folder from geth
 ///
@@ -29,17 +34,101 @@ pub enum Freezer {
 
 /// The index struct
 ///
-/// Used to store ancient chaindata folder as well as filenumbers and offsets.
+/// Used to store ancient chaindata folder as well as the location of every requested item.
+/// Items are stored in ascending block order, starting with `first_block`.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Index {
     pub ancient_folder: PathBuf,
-    pub offsets: HashMap<u16, Vec<u64>>,
+    pub first_block: u64,
+    pub items: Vec<ItemSpan>,
+}
+
+impl Index {
+    /// Groups consecutive items which are stored in the same data file
+    ///
+    /// The groups are returned in ascending block order.
+    pub fn files(&self) -> impl Iterator<Item = &[ItemSpan]> {
+        self.items.chunk_by(|a, b| a.file_number == b.file_number)
+    }
+}
+
+/// A single entry of an index file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexEntry {
+    pub file_number: u16,
+    pub offset: u32,
+}
+
+impl IndexEntry {
+    /// Parses an index entry from its 6 raw bytes
+    pub(crate) fn from_bytes(raw: &[u8]) -> Result<Self, FreezerError> {
+        let file_number = u16_from_bytes_be(&raw[..FILE_NUMBER_BYTE_SIZE as usize])
+            .map_err(FreezerError::Conversion)?;
+        let offset =
+            u32_from_bytes_be(&raw[FILE_NUMBER_BYTE_SIZE as usize..INDEX_ENTRY_SIZE as usize])
+                .map_err(FreezerError::Conversion)?;
+        Ok(IndexEntry {
+            file_number,
+            offset,
+        })
+    }
+}
+
+/// The location of a single item in the data files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ItemSpan {
+    pub file_number: u16,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ItemSpan {
+    /// Builds the item location from two consecutive index entries
+    ///
+    /// Follows the rule of geth: if the end entry points into another data file, the item is
+    /// stored at the beginning of that file.
+    pub fn new(start: IndexEntry, end: IndexEntry) -> Self {
+        if start.file_number != end.file_number {
+            return ItemSpan {
+                file_number: end.file_number,
+                start: 0,
+                end: end.offset as u64,
+            };
+        }
+        ItemSpan {
+            file_number: start.file_number,
+            start: start.offset as u64,
+            end: end.offset as u64,
+        }
+    }
+
+    /// The size of the item in bytes
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Returns true if the item has no bytes
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
 }
 
 impl Freezer {
+    /// All freezer tables
+    pub const ALL: [Freezer; 5] = [
+        Self::Headers,
+        Self::Hashes,
+        Self::Bodies,
+        Self::Receipts,
+        Self::Difficulty,
+    ];
+
     /// Loads the index file into memory
     ///
-    /// This index will contain the offsets and file numbers we need to load the raw block data.
+    /// The returned index contains the folder of the table, which has to be used for
+    /// [`Freezer::load_data`].
+    /// This index will contain the file number, start and end offset of every item we need to load
+    /// the raw block data.
     pub fn init(
         &self,
         ancient_folder: &Path,
@@ -55,54 +144,34 @@ impl Freezer {
         );
 
         info!("Reading index...");
-        // Calculate some constans we need and open index file
-        let index_size =
-            (FILE_NUMBER_BYTE_SIZE + OFFSET_NUMBER_BYTE_SIZE) * (max_block - min_block);
-        let index_offset = (FILE_NUMBER_BYTE_SIZE + OFFSET_NUMBER_BYTE_SIZE) * min_block;
-        let index_filename = ancient_folder.join(self.index_filename());
-        let mut index_file = File::open(index_filename).map_err(FreezerError::OpenFile)?;
-
-        // Load the part of the index we need into a byte buffer
-        let mut raw_index: Vec<u8> = Vec::with_capacity(index_size as usize);
-        let _ = index_file
-            .seek(SeekFrom::Start(index_offset))
-            .map_err(FreezerError::SeekFile)?;
-        let _ = index_file
-            .take(index_size + FILE_NUMBER_BYTE_SIZE + OFFSET_NUMBER_BYTE_SIZE)
-            .read_to_end(&mut raw_index)
-            .map_err(FreezerError::ReadFile)?;
-
-        let mut offsets = HashMap::<u16, Vec<u64>>::new();
-
-        // Convert raw index bytes into file number and offsets
-        for chunk in raw_index.chunks((FILE_NUMBER_BYTE_SIZE + OFFSET_NUMBER_BYTE_SIZE) as usize) {
-            let file_number = u16_from_bytes_be(&chunk[..FILE_NUMBER_BYTE_SIZE as usize])
-                .map_err(FreezerError::Conversion)?;
-            let offset = u32_from_bytes_be(&chunk[FILE_NUMBER_BYTE_SIZE as usize..])
-                .map_err(FreezerError::Conversion)? as u64;
-
-            offsets
-                .entry(file_number)
-                .or_insert_with(Vec::new)
-                .push(offset);
-        }
+        let mut table = FreezerTable::open(ancient_folder, *self)?;
+        let items = table.read_index(min_block..max_block)?;
 
         let index = Index {
-            ancient_folder: ancient_folder.into(),
-            offsets,
+            ancient_folder: table.ancient_folder().into(),
+            first_block: min_block,
+            items,
         };
         Ok(index)
     }
 
+    /// Describes the table of this freezer variant
+    ///
+    /// Returns the index size, number of items, available blocks and data files of the table.
+    pub fn info(&self, ancient_folder: &Path) -> Result<TableInfo, FreezerError> {
+        FreezerTable::open(ancient_folder, *self)?.info()
+    }
+
     /// Loads the raw block data
     ///
+    /// All items have to be stored in the same data file, see [`Index::files`].
     /// Returns a byte vector containing the requested block data
     pub fn load_data(
         &self,
         ancient_folder: &Path,
-        file_number: u16,
-        offsets: &[u64],
+        items: &[ItemSpan],
     ) -> Result<Vec<u8>, FreezerError> {
+        let file_number = items.first().ok_or(FreezerError::BlockOffset)?.file_number;
         info!(
             "Reading raw block data from {}.",
             self.data_filename(file_number)
@@ -115,8 +184,8 @@ impl Freezer {
         let read_bytes = seek_and_read(
             &mut data_file,
             &mut block_data,
-            *offsets.first().ok_or(FreezerError::BlockOffset)?,
-            *offsets.last().ok_or(FreezerError::BlockOffset)?,
+            items.first().ok_or(FreezerError::BlockOffset)?.start,
+            items.last().ok_or(FreezerError::BlockOffset)?.end,
         )?;
         debug!("Read {} bytes of data", read_bytes);
         Ok(block_data)
@@ -127,49 +196,19 @@ impl Freezer {
     /// Returns a string
     pub fn export_json<T: DeserializeOwned + Display + Serialize>(
         &self,
-        block_offsets: &[u64],
+        items: &[ItemSpan],
         block_data: &[u8],
     ) -> Result<String, FreezerError> {
         info!("Decompressing and deserializing output.");
-        let offset_offset = block_offsets.first().ok_or(FreezerError::BlockOffset)?;
+        let offset_offset = items.first().ok_or(FreezerError::BlockOffset)?.start;
         let mut block_objects = String::new();
 
-        // This closure decompresses snappy if necessary
-        let decompressor = |input: &[u8]| -> Result<Vec<u8>, FreezerError> {
-            if self.is_compressed() {
-                trace!("Decompressing...");
-                Decoder::new()
-                    .decompress_vec(input)
-                    .map_err(FreezerError::SnappyDecompress)
-            } else {
-                trace!("Input is not compressed.");
-                Ok(input.to_vec())
-            }
-        };
-
-        // This closure deserializes the rlp bytes
-        let rlp_deserialize = |input: &[u8]| -> Result<T, FreezerError> {
-            trace!("Deserializing...");
-            // Ugly hack to adapt hashes in freezer to RLP format. Somehow geth does not export
-            // hashes to the freezer in correct RLP format
-            let mut tmp = vec![];
-            let input = if *self == Freezer::Hashes {
-                tmp.extend_from_slice(&[&[0xa0_u8], input].concat());
-                tmp.as_slice()
-            } else {
-                input
-            };
-            let mut deserializer =
-                RlpDeserializer::new(input).map_err(FreezerError::RlpDeserialization)?;
-            T::deserialize(&mut deserializer).map_err(FreezerError::RlpDeserialization)
-        };
-
-        for (k, offsets) in block_offsets.windows(2).enumerate() {
-            let blob = decompressor(
+        for (k, item) in items.iter().enumerate() {
+            let blob = self.decompress(
                 &block_data
-                    [(offsets[0] - offset_offset) as usize..(offsets[1] - offset_offset) as usize],
+                    [(item.start - offset_offset) as usize..(item.end - offset_offset) as usize],
             )?;
-            block_objects.push_str(&(rlp_deserialize(&blob)?.to_string() + ",\n"));
+            block_objects.push_str(&(self.deserialize::<T>(&blob)?.to_string() + ",\n"));
 
             if k % 10000 == 0 && k > 0 {
                 info!("Processed {}k blocks.", k / 1000);
@@ -178,7 +217,40 @@ impl Freezer {
         Ok(block_objects)
     }
 
-    const fn index_filename(&self) -> &'static str {
+    /// Decompresses snappy if necessary
+    ///
+    /// Uncompressed input is borrowed without copying it.
+    pub(crate) fn decompress<'a>(&self, input: &'a [u8]) -> Result<Cow<'a, [u8]>, FreezerError> {
+        if self.is_compressed() {
+            trace!("Decompressing...");
+            Decoder::new()
+                .decompress_vec(input)
+                .map(Cow::Owned)
+                .map_err(FreezerError::SnappyDecompress)
+        } else {
+            trace!("Input is not compressed.");
+            Ok(Cow::Borrowed(input))
+        }
+    }
+
+    /// Deserializes the decompressed rlp bytes of a single item
+    pub(crate) fn deserialize<T: DeserializeOwned>(&self, input: &[u8]) -> Result<T, FreezerError> {
+        trace!("Deserializing...");
+        // Ugly hack to adapt hashes in freezer to RLP format. Somehow geth does not export
+        // hashes to the freezer in correct RLP format
+        let mut tmp = vec![];
+        let input = if *self == Freezer::Hashes {
+            tmp.extend_from_slice(&[&[0xa0_u8], input].concat());
+            tmp.as_slice()
+        } else {
+            input
+        };
+        let mut deserializer =
+            RlpDeserializer::new(input).map_err(FreezerError::RlpDeserialization)?;
+        T::deserialize(&mut deserializer).map_err(FreezerError::RlpDeserialization)
+    }
+
+    pub(crate) const fn index_filename(&self) -> &'static str {
         match *self {
             Self::Bodies => "bodies.cidx",
             Self::Headers => "headers.cidx",
@@ -188,7 +260,34 @@ impl Freezer {
         }
     }
 
-    fn data_filename(&self, file_number: u16) -> String {
+    pub(crate) fn meta_filename(&self) -> String {
+        format!("{}.meta", self.table_name())
+    }
+
+    /// Returns the folder which contains the files of this table
+    ///
+    /// Newer geth versions store the chain freezer in the `chain` subfolder of the ancient folder.
+    pub fn table_folder(&self, ancient_folder: &Path) -> PathBuf {
+        let chain_folder = ancient_folder.join(CHAIN_FOLDER);
+        if !ancient_folder.join(self.index_filename()).exists()
+            && chain_folder.join(self.index_filename()).exists()
+        {
+            return chain_folder;
+        }
+        ancient_folder.into()
+    }
+
+    pub(crate) const fn table_name(&self) -> &'static str {
+        match *self {
+            Self::Bodies => "bodies",
+            Self::Headers => "headers",
+            Self::Hashes => "hashes",
+            Self::Difficulty => "diffs",
+            Self::Receipts => "receipts",
+        }
+    }
+
+    pub(crate) fn data_filename(&self, file_number: u16) -> String {
         match *self {
             Self::Bodies => format!("bodies.{:04}.cdat", file_number),
             Self::Headers => format!("headers.{:04}.cdat", file_number),
@@ -198,7 +297,7 @@ impl Freezer {
         }
     }
 
-    const fn is_compressed(&self) -> bool {
+    pub(crate) const fn is_compressed(&self) -> bool {
         match *self {
             Self::Bodies => true,
             Self::Headers => true,
@@ -251,12 +350,21 @@ pub enum FreezerError {
     SeekFile(#[source] std::io::Error),
     #[error("Cannot read from file, {0}")]
     ReadFile(#[source] std::io::Error),
+    #[error("Cannot memory-map file, {0}")]
+    MapFile(#[source] std::io::Error),
     #[error("Unable to convert raw bytes into block offsets, {0}")]
     Conversion(#[source] NumericError),
     #[error("Unable to read file metadata, {0}")]
     FileMetadata(#[source] std::io::Error),
     #[error("Cannot determine block offset")]
     BlockOffset,
+    #[error("Block {0} is out of bounds, the table only contains {1} items")]
+    OutOfBounds(u64, u64),
+    #[error("Block {0} has been pruned, the table starts at block {1}")]
+    Pruned(u64, u64),
+    #[cfg(feature = "parallel")]
+    #[error("Unable to build thread pool, {0}")]
+    ThreadPool(#[source] rayon::ThreadPoolBuildError),
     #[error("Read error during decompression, {0}")]
     SnappyDecompress(#[source] snap::Error),
     #[error("Error during rlp deserialization, {0}")]
@@ -266,6 +374,7 @@ pub enum FreezerError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::BlockHeader;
     use std::path::PathBuf;
 
     #[test]
@@ -278,4 +387,72 @@ mod tests {
         let expected: Vec<u8> = vec![3, 8, 194, 192, 192, 3, 8, 194, 192, 192];
         assert_eq!(buffer, expected);
     }
+
+    #[test]
+    fn test_freezer_item_span() {
+        let start = IndexEntry {
+            file_number: 0,
+            offset: 2847,
+        };
+        let same_file = IndexEntry {
+            file_number: 0,
+            offset: 3000,
+        };
+        let next_file = IndexEntry {
+            file_number: 1,
+            offset: 291,
+        };
+
+        assert_eq!(
+            ItemSpan::new(start, same_file),
+            ItemSpan {
+                file_number: 0,
+                start: 2847,
+                end: 3000
+            }
+        );
+        assert_eq!(
+            ItemSpan::new(start, next_file),
+            ItemSpan {
+                file_number: 1,
+                start: 0,
+                end: 291
+            }
+        );
+    }
+
+    #[test]
+    fn test_freezer_export_across_data_files() {
+        // The split fixture contains headers 0-20, blocks 0-9 are stored in headers.0000.cdat and
+        // blocks 10-20 in headers.0001.cdat
+        let split_folder = PathBuf::from("./fixtures/split");
+        let single_folder = PathBuf::from("./fixtures/headers");
+
+        let index = Freezer::Headers.init(&split_folder, 5, 15).unwrap();
+        assert_eq!(index.items.len(), 10);
+        assert_eq!(
+            index.files().map(|items| items.len()).collect::<Vec<_>>(),
+            vec![5, 5]
+        );
+
+        let mut split_export = String::new();
+        for items in index.files() {
+            let data = Freezer::Headers.load_data(&split_folder, items).unwrap();
+            split_export.push_str(
+                &Freezer::Headers
+                    .export_json::<BlockHeader>(items, &data)
+                    .unwrap(),
+            );
+        }
+
+        let index = Freezer::Headers.init(&single_folder, 5, 15).unwrap();
+        let data = Freezer::Headers
+            .load_data(&single_folder, &index.items)
+            .unwrap();
+        let single_export = Freezer::Headers
+            .export_json::<BlockHeader>(&index.items, &data)
+            .unwrap();
+
+        assert_eq!(split_export, single_export);
+    }
 }
@@ -1,6 +1,23 @@
+pub mod crypto;
 pub mod extract;
+pub mod logs;
 pub mod numeric;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod reader;
 pub mod rlp;
+pub mod table;
+pub mod trie;
 pub mod types;
+pub mod verify;
 
 pub use extract::{Freezer, FreezerError};
+pub use logs::{LogIter, LogReader};
+#[cfg(feature = "parallel")]
+pub use parallel::ParFreezerIter;
+pub use reader::{BlockIter, BlockReader};
+pub use table::{FreezerIter, FreezerTable, ReadBackend};
+pub use verify::{
+    DifficultyVerifier, DifficultyVerifyIter, HeaderVerifier, HeaderVerifyIter, Mismatch, Verifier,
+    VerifyIter, Violation,
+};
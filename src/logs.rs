@@ -0,0 +1,120 @@
+use crate::extract::{Freezer, FreezerError};
+use crate::table::{FreezerTable, ReadBackend};
+use crate::types::{BlockHeader, LogFilter, Receipts};
+use std::ops::Range;
+use std::path::Path;
+
+/// Reads the receipts of blocks which contain logs matching a [`LogFilter`]
+///
+/// The bloom of the header is checked first, so the receipts are only decoded for blocks which
+/// can contain a matching log.
+#[derive(Debug)]
+pub struct LogReader {
+    headers: FreezerTable,
+    receipts: FreezerTable,
+    filter: LogFilter,
+}
+
+impl LogReader {
+    /// Opens the `headers` and `receipts` tables of the freezer
+    pub fn open(ancient_folder: &Path, filter: LogFilter) -> Result<Self, FreezerError> {
+        Self::open_with_backend(ancient_folder, filter, ReadBackend::default())
+    }
+
+    /// Opens the `headers` and `receipts` tables of the freezer with the given read backend
+    pub fn open_with_backend(
+        ancient_folder: &Path,
+        filter: LogFilter,
+        backend: ReadBackend,
+    ) -> Result<Self, FreezerError> {
+        let open = |freezer| FreezerTable::open_with_backend(ancient_folder, freezer, backend);
+        Ok(LogReader {
+            headers: open(Freezer::Headers)?,
+            receipts: open(Freezer::Receipts)?,
+            filter,
+        })
+    }
+
+    /// Reads the receipts of a single block if it contains a matching log
+    ///
+    /// Returns `None` if the bloom of the header rules out a match or if the match of the bloom
+    /// was a false positive.
+    pub fn get(&mut self, block_number: u64) -> Result<Option<Receipts>, FreezerError> {
+        let header: BlockHeader = self.headers.get(block_number)?;
+        if !self.filter.may_match(&header.logs_bloom) {
+            return Ok(None);
+        }
+        let receipts: Receipts = self.receipts.get(block_number)?;
+        Ok(receipts.contains_log(&self.filter).then_some(receipts))
+    }
+
+    /// Returns an iterator over the matching blocks of a range
+    ///
+    /// The iterator yields the block number together with the receipts and skips blocks
+    /// without a matching log. It stops after the first error.
+    pub fn iter_range(&mut self, range: Range<u64>) -> LogIter<'_> {
+        LogIter {
+            reader: self,
+            range,
+        }
+    }
+}
+
+/// Iterator over the matching blocks of a [`LogReader`]
+#[derive(Debug)]
+pub struct LogIter<'a> {
+    reader: &'a mut LogReader,
+    range: Range<u64>,
+}
+
+impl<'a> Iterator for LogIter<'a> {
+    type Item = Result<(u64, Receipts), FreezerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let block_number = self.range.next()?;
+            match self.reader.get(block_number) {
+                Ok(Some(receipts)) => return Some(Ok((block_number, receipts))),
+                Ok(None) => continue,
+                Err(err) => {
+                    self.range = self.range.end..self.range.end;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.range.size_hint().1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ByteArray;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_log_reader() {
+        // The first blocks of mainnet have no logs, so not even the empty filter matches them
+        let ancient_folder = PathBuf::from("./fixtures/full");
+        let mut reader = LogReader::open(&ancient_folder, LogFilter::default()).unwrap();
+        assert_eq!(reader.get(1).unwrap(), None);
+
+        let filter = LogFilter {
+            address: Some(ByteArray::<20>([0x11; 20])),
+            topics: vec![],
+        };
+        let mut reader = LogReader::open(&ancient_folder, filter).unwrap();
+        let results = reader
+            .iter_range(0..3)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(results.is_empty());
+        assert!(matches!(
+            reader.iter_range(2..4).next(),
+            Some(Err(FreezerError::OutOfBounds(3, 3)))
+        ));
+    }
+}
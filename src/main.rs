@@ -1,11 +1,55 @@
 use log::info;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
 use simplelog::{ColorChoice, Config, LevelFilter, TermLogger, TerminalMode};
+use std::io::Write;
+use std::ops::Range;
 use std::path::Path;
-use xtralib::types::{BlockBody, BlockHash, BlockHeader, Receipts, TotalDifficulty};
-use xtralib::Freezer;
+use xtralib::types::{
+    BlockBody, BlockHash, BlockHeader, BlockItem, ByteArray, LogFilter, NiceVec, NumberedBody,
+    Receipts, TotalDifficulty, Withdrawal,
+};
+use xtralib::{
+    BlockReader, DifficultyVerifier, Freezer, FreezerError, FreezerTable, HeaderVerifier,
+    LogReader, ReadBackend, Verifier,
+};
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
+
+    // Optional flags are removed before the positional arguments are parsed
+    let threads = match parse_threads(&mut args) {
+        Some(threads) => threads,
+        None => {
+            println!("Invalid number of threads\n");
+            print_info();
+            return;
+        }
+    };
+    let backend = parse_backend(&mut args);
+    let filter = match parse_filter(&mut args) {
+        Some(filter) => filter,
+        None => {
+            println!("Invalid address or topic\n");
+            print_info();
+            return;
+        }
+    };
+    #[cfg(not(feature = "parallel"))]
+    if threads.is_some() {
+        eprintln!("xtra was built without the parallel feature, ignoring --threads");
+    }
+
+    // The header and difficulty verification modes can be given as two words
+    if args.len() == 6 && args[2] == "verify" && ["headers", "difficulty"].contains(&&*args[3]) {
+        let part = args.remove(3);
+        args[2] = format!("verify-{}", part);
+    }
+
+    if args.len() == 3 && args[2] == "info" {
+        print_freezer_info(Path::new(&args[1]));
+        return;
+    }
 
     if args.len() != 5 || ["--help", "-help", "-h", "h"].contains(&args[1].as_str()) {
         print_info();
@@ -13,9 +57,9 @@ fn main() {
     }
 
     let ancient_folder = Path::new(&args[1]);
-    let block_part = parse_block_part(&args[2]);
+    let mode = parse_mode(&args[2]);
     let block_numbers = parse_block_numbers(&args[3]);
-    let mut write_target: Box<dyn std::io::Write> = match args[4].as_str() {
+    let mut write_target: Box<dyn Write> = match args[4].as_str() {
         "-" => Box::new(std::io::stdout()),
         file => Box::new(std::fs::File::create(file).expect("Cannot create file")),
     };
@@ -31,40 +75,321 @@ fn main() {
         None
     };
 
-    if block_part.is_none() || block_numbers.is_none() {
+    if mode.is_none() || block_numbers.is_none() {
         println!("Invalid input. Please supply a valid combination of arguments\n");
         print_info();
         return;
     }
-    let block_part = block_part.unwrap();
+    let mode = mode.unwrap();
     let block_numbers = block_numbers.unwrap();
 
-    // Build index
-    let index = block_part
-        .init(ancient_folder, block_numbers.0, block_numbers.1)
-        .expect("Failed to build index");
-
-    // Load all data files into RAM
-    let _ = write_target.write_all(b"[\n");
-    for job in index.offsets {
-        let data = block_part
-            .load_data(ancient_folder, job.0, &job.1)
-            .expect("Unable to load data files");
-        let output = match block_part {
-            Freezer::Bodies => block_part.export_json::<BlockBody>(&job.1, &data),
-            Freezer::Headers => block_part.export_json::<BlockHeader>(&job.1, &data),
-            Freezer::Hashes => block_part.export_json::<BlockHash>(&job.1, &data),
-            Freezer::Difficulty => block_part.export_json::<TotalDifficulty>(&job.1, &data),
-            Freezer::Receipts => block_part.export_json::<Receipts>(&job.1, &data),
-        };
-        info!("Writing to hard disk...");
-        let _ = write_target.write_all(output.expect("Unable to export data").as_bytes());
-        info!("Done!");
+    if filter != LogFilter::default() && !matches!(mode, Mode::Logs) {
+        println!("The --address and --topic flags are only supported in the logs mode\n");
+        print_info();
+        return;
+    }
+
+    let (min_block, max_block) = block_numbers;
+    if min_block >= max_block {
+        println!("Invalid block range. Minimum block must be smaller than maximum block\n");
+        print_info();
+        return;
+    }
+
+    // Stream the blocks one by one in ascending order
+    let range = min_block..max_block;
+    let result = match mode {
+        Mode::Part(block_part) => {
+            info!(
+                "Attempting to read blocks {}-{} from freezer {}.",
+                min_block, max_block, block_part
+            );
+            let mut table = FreezerTable::open_with_backend(ancient_folder, block_part, backend)
+                .expect("Failed to open freezer table");
+            match block_part {
+                Freezer::Bodies => export_bodies(&mut table, range, threads, &mut write_target),
+                Freezer::Headers => {
+                    export_part::<HashedHeader>(&mut table, range, threads, &mut write_target)
+                }
+                Freezer::Hashes => {
+                    export_part::<BlockHash>(&mut table, range, threads, &mut write_target)
+                }
+                Freezer::Difficulty => {
+                    export_part::<TotalDifficulty>(&mut table, range, threads, &mut write_target)
+                }
+                Freezer::Receipts => {
+                    export_part::<Receipts>(&mut table, range, threads, &mut write_target)
+                }
+            }
+        }
+        Mode::Block => {
+            info!(
+                "Attempting to read full blocks {}-{} from all freezer tables.",
+                min_block, max_block
+            );
+            let mut reader = BlockReader::open_with_backend(ancient_folder, backend)
+                .expect("Failed to open freezer tables");
+            write_json(reader.iter_range(range), &mut write_target)
+        }
+        Mode::Withdrawals => {
+            info!(
+                "Attempting to read withdrawals of blocks {}-{} from freezer {}.",
+                min_block,
+                max_block,
+                Freezer::Bodies
+            );
+            let mut table =
+                FreezerTable::open_with_backend(ancient_folder, Freezer::Bodies, backend)
+                    .expect("Failed to open freezer table");
+            export_withdrawals(&mut table, range, threads, &mut write_target)
+        }
+        Mode::Verify => {
+            info!(
+                "Attempting to verify blocks {}-{} against the freezer tables.",
+                min_block, max_block
+            );
+            let mut verifier = Verifier::open_with_backend(ancient_folder, backend)
+                .expect("Failed to open freezer tables");
+            write_report(verifier.iter_range(range), &mut write_target)
+        }
+        Mode::VerifyHeaders => {
+            info!(
+                "Attempting to verify the consensus rules of headers {}-{}.",
+                min_block, max_block
+            );
+            let mut verifier = HeaderVerifier::open_with_backend(ancient_folder, backend)
+                .expect("Failed to open freezer table");
+            write_report(verifier.iter_range(range), &mut write_target)
+        }
+        Mode::VerifyDifficulty => {
+            info!(
+                "Attempting to verify the difficulties of blocks {}-{}.",
+                min_block, max_block
+            );
+            let mut verifier = DifficultyVerifier::open_with_backend(ancient_folder, backend)
+                .expect("Failed to open freezer tables");
+            write_report(verifier.iter_range(range), &mut write_target)
+        }
+        Mode::Logs => {
+            info!(
+                "Attempting to read receipts of blocks {}-{} with logs matching the filter.",
+                min_block, max_block
+            );
+            let mut reader = LogReader::open_with_backend(ancient_folder, filter, backend)
+                .expect("Failed to open freezer tables");
+            write_json(reader.iter_range(range), &mut write_target)
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("Unable to export data: {}", err);
+        std::process::exit(1);
     }
-    let _ = write_target.write_all(b"]");
     info!("Finished successfully!");
 }
 
+/// What the user wants to export
+enum Mode {
+    Part(Freezer),
+    Block,
+    Withdrawals,
+    Verify,
+    VerifyHeaders,
+    VerifyDifficulty,
+    Logs,
+}
+
+/// A header annotated with its hash, so that the hashes table is not needed
+#[derive(Serialize)]
+struct HashedHeader {
+    hash: ByteArray<32>,
+    #[serde(flatten)]
+    header: BlockHeader,
+}
+
+impl<'de> Deserialize<'de> for HashedHeader {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let header = BlockHeader::deserialize(deserializer)?;
+        Ok(HashedHeader {
+            hash: header.hash(),
+            header,
+        })
+    }
+}
+
+fn export_part<T: DeserializeOwned + Serialize + Send>(
+    table: &mut FreezerTable,
+    range: Range<u64>,
+    threads: Option<usize>,
+    write_target: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "parallel")]
+    if let Some(threads) = threads {
+        return write_json(table.par_iter_range::<T>(range, threads)?, write_target);
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = threads;
+    write_json(table.iter_range::<T>(range), write_target)
+}
+
+fn export_bodies(
+    table: &mut FreezerTable,
+    range: Range<u64>,
+    threads: Option<usize>,
+    write_target: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "parallel")]
+    if let Some(threads) = threads {
+        let bodies = table.par_iter_range::<BlockBody>(range, threads)?;
+        return write_json(numbered_bodies(bodies), write_target);
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = threads;
+    write_json(numbered_bodies(table.iter_range(range)), write_target)
+}
+
+// The block number is needed for the inclusion distances of the uncles
+fn numbered_bodies(
+    bodies: impl Iterator<Item = Result<(u64, BlockBody), FreezerError>>,
+) -> impl Iterator<Item = Result<(u64, NumberedBody), FreezerError>> {
+    bodies.map(|body| {
+        body.map(|(block_number, body)| (block_number, NumberedBody { block_number, body }))
+    })
+}
+
+fn export_withdrawals(
+    table: &mut FreezerTable,
+    range: Range<u64>,
+    threads: Option<usize>,
+    write_target: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "parallel")]
+    if let Some(threads) = threads {
+        let bodies = table.par_iter_range::<BlockBody>(range, threads)?;
+        return write_json(withdrawals(bodies), write_target);
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = threads;
+    write_json(withdrawals(table.iter_range(range)), write_target)
+}
+
+// Skips the blocks before the Shanghai hard fork, which have no withdrawals
+fn withdrawals(
+    bodies: impl Iterator<Item = Result<(u64, BlockBody), FreezerError>>,
+) -> impl Iterator<Item = Result<(u64, NiceVec<Withdrawal>), FreezerError>> {
+    bodies.filter_map(|body| match body {
+        Ok((block_number, body)) => body
+            .withdrawals
+            .map(|withdrawals| Ok((block_number, withdrawals))),
+        Err(err) => Some(Err(err)),
+    })
+}
+
+// Only blocks with mismatches or violations are written, the export fails if there are any
+fn write_report<T: Serialize>(
+    reports: impl Iterator<Item = Result<(u64, NiceVec<T>), FreezerError>>,
+    write_target: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut failed_blocks = 0;
+    let reports = reports
+        .filter(|report| !matches!(report, Ok((_, findings)) if findings.0.is_empty()))
+        .inspect(|report| failed_blocks += report.is_ok() as u64);
+    write_json(reports, write_target)?;
+    if failed_blocks > 0 {
+        return Err(format!("{} blocks failed verification", failed_blocks).into());
+    }
+    Ok(())
+}
+
+fn write_json<T: Serialize>(
+    items: impl Iterator<Item = Result<(u64, T), FreezerError>>,
+    write_target: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    write_target.write_all(b"[\n")?;
+    for (k, item) in items.enumerate() {
+        let (block_number, data) = item?;
+        if k > 0 {
+            write_target.write_all(b",\n")?;
+        }
+        let output = BlockItem { block_number, data };
+        write_target.write_all(output.to_string().as_bytes())?;
+
+        if k % 10000 == 0 && k > 0 {
+            info!("Processed {}k blocks.", k / 1000);
+        }
+    }
+    write_target.write_all(b"\n]")?;
+    Ok(())
+}
+
+fn print_freezer_info(ancient_folder: &Path) {
+    let mut tables = vec![];
+    for freezer in Freezer::ALL.iter() {
+        match freezer.info(ancient_folder) {
+            Ok(info) => tables.push(info.to_string()),
+            Err(err) => eprintln!("Unable to read freezer table {}: {}", freezer, err),
+        }
+    }
+    println!("[\n{}\n]", tables.join(",\n"));
+}
+
+// Returns `None` if the flag has no value or the value is not a positive number
+fn parse_threads(args: &mut Vec<String>) -> Option<Option<usize>> {
+    let position = match args.iter().position(|arg| arg == "--threads") {
+        Some(position) => position,
+        None => return Some(None),
+    };
+    let threads = args
+        .get(position + 1)?
+        .parse::<usize>()
+        .ok()
+        .filter(|&threads| threads > 0)?;
+    let _ = args.drain(position..position + 2);
+    Some(Some(threads))
+}
+
+fn parse_backend(args: &mut Vec<String>) -> ReadBackend {
+    match args.iter().position(|arg| arg == "--mmap") {
+        Some(position) => {
+            let _ = args.remove(position);
+            ReadBackend::Mmap
+        }
+        None => ReadBackend::File,
+    }
+}
+
+// Both flags are optional, an address can be given once and a topic multiple times
+fn parse_filter(args: &mut Vec<String>) -> Option<LogFilter> {
+    let mut filter = LogFilter::default();
+    while let Some(position) = args.iter().position(|arg| arg == "--address") {
+        let value = args.get(position + 1)?;
+        if filter.address.is_some() {
+            return None;
+        }
+        filter.address = Some(parse_hex(value)?);
+        let _ = args.drain(position..position + 2);
+    }
+    while let Some(position) = args.iter().position(|arg| arg == "--topic") {
+        filter.topics.push(parse_hex(args.get(position + 1)?)?);
+        let _ = args.drain(position..position + 2);
+    }
+    Some(filter)
+}
+
+fn parse_hex<const N: usize>(hex: &str) -> Option<ByteArray<N>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() != 2 * N || !hex.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0_u8; N];
+    for (k, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * k..2 * k + 2], 16).ok()?;
+    }
+    Some(ByteArray::<N>(bytes))
+}
+
 fn parse_block_numbers(block_numbers: &str) -> Option<(u64, u64)> {
     let block_numbers = block_numbers
         .split('-')
@@ -81,13 +406,19 @@ fn parse_block_numbers(block_numbers: &str) -> Option<(u64, u64)> {
     None
 }
 
-fn parse_block_part(block_part: &str) -> Option<Freezer> {
-    match block_part {
-        "b" | "body" => Some(Freezer::Bodies),
-        "h" | "header" => Some(Freezer::Headers),
-        "d" | "difficulty" => Some(Freezer::Difficulty),
-        "hash" => Some(Freezer::Hashes),
-        "r" | "receipt" => Some(Freezer::Receipts),
+fn parse_mode(mode: &str) -> Option<Mode> {
+    match mode {
+        "b" | "body" => Some(Mode::Part(Freezer::Bodies)),
+        "h" | "header" => Some(Mode::Part(Freezer::Headers)),
+        "d" | "difficulty" => Some(Mode::Part(Freezer::Difficulty)),
+        "hash" => Some(Mode::Part(Freezer::Hashes)),
+        "r" | "receipt" => Some(Mode::Part(Freezer::Receipts)),
+        "f" | "full" => Some(Mode::Block),
+        "w" | "withdrawals" => Some(Mode::Withdrawals),
+        "v" | "verify" => Some(Mode::Verify),
+        "vh" | "verify-headers" => Some(Mode::VerifyHeaders),
+        "vd" | "verify-difficulty" => Some(Mode::VerifyDifficulty),
+        "l" | "logs" => Some(Mode::Logs),
         _ => None,
     }
 }
@@ -95,16 +426,31 @@ fn parse_block_part(block_part: &str) -> Option<Freezer> {
 fn print_info() {
     println!(
         r#"
-Usage: xtra FOLDER MODE BLOCK_RANGE OUTPUT
+Usage: xtra FOLDER MODE BLOCK_RANGE OUTPUT [--threads THREADS] [--mmap]
+                                          [--address ADDRESS] [--topic TOPIC]...
+       xtra FOLDER info
 
 FOLDER              the geth freezer folder, usually chaindata/ancient
 
 MODE
-    b, body         export block bodies
-    h, header       export block headers
+    b, body         export block bodies, with the hashes and inclusion distances of uncles
+    h, header       export block headers together with their computed hashes
     d, difficulty   export total difficulty
     hash            export block hashes
     r, receipt      export transaction receipts
+    f, full         export full blocks joined from all tables
+    w, withdrawals  export the withdrawals of blocks since the Shanghai hard fork
+    v, verify       recompute block hashes, the transactions, receipts and withdrawals roots,
+                    the uncles hash and the logs bloom, and export the blocks which do not
+                    match the freezer tables
+    vh, verify headers
+                    check parent hashes, numbers, timestamps, gas limits, gas used and extra
+                    data against the consensus rules, and export the violating headers
+    vd, verify difficulty
+                    recompute difficulties with the mainnet fork rules up to the merge and
+                    total difficulties, and export the blocks which do not match the freezer
+    l, logs         export the receipts of blocks with a log matching --address and --topic,
+                    blocks are skipped by their header bloom without decoding the receipts
 
 BLOCK_RANGE
     number          export the single block with this number
@@ -113,6 +459,14 @@ BLOCK_RANGE
 OUTPUT
     -               print to stdout
     file            write to file
+
+--threads THREADS   decode items on this many threads (at least 1), requires the parallel
+                    feature
+--address ADDRESS   only match logs emitted by this address, logs mode only
+--topic TOPIC       only match logs with this topic, can be repeated, logs mode only
+--mmap              memory-map the freezer files instead of reading them, geth must not be running
+
+info                describe the tables, items and data files of the freezer folder
     "#
     );
 }
@@ -72,6 +72,7 @@ pub enum NumericError {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
     use super::*;
 
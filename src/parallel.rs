@@ -0,0 +1,143 @@
+use crate::extract::FreezerError;
+use crate::table::FreezerTable;
+use log::debug;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::de::DeserializeOwned;
+use std::ops::Range;
+
+// The number of items per thread which are read from disk before they are decoded in parallel
+const ITEMS_PER_THREAD: u64 = 256;
+
+impl FreezerTable {
+    /// Returns an iterator over a range of blocks, which decodes the items on multiple threads
+    ///
+    /// Items are read from disk in batches, decompressed and deserialized on a thread pool with
+    /// the given number of threads and yielded in ascending block order, just like
+    /// [`FreezerTable::iter_range`]. Zero threads let rayon choose one thread per CPU core.
+    pub fn par_iter_range<T: DeserializeOwned + Send>(
+        &mut self,
+        range: Range<u64>,
+        threads: usize,
+    ) -> Result<ParFreezerIter<'_, T>, FreezerError> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(FreezerError::ThreadPool)?;
+        let batch_size = ITEMS_PER_THREAD * pool.current_num_threads() as u64;
+        debug!(
+            "Decoding {} on {} threads.",
+            self.freezer(),
+            pool.current_num_threads()
+        );
+
+        Ok(ParFreezerIter {
+            table: self,
+            range,
+            pool,
+            batch_size,
+            batch: vec![].into_iter(),
+        })
+    }
+}
+
+/// Iterator over a range of items of a [`FreezerTable`], which decodes items in parallel
+///
+/// See [`FreezerTable::par_iter_range`].
+#[derive(Debug)]
+pub struct ParFreezerIter<'a, T> {
+    table: &'a mut FreezerTable,
+    range: Range<u64>,
+    pool: ThreadPool,
+    batch_size: u64,
+    batch: std::vec::IntoIter<Result<(u64, T), FreezerError>>,
+}
+
+impl<'a, T: DeserializeOwned + Send> ParFreezerIter<'a, T> {
+    // Reads the next batch of items and decodes them on the thread pool
+    fn next_batch(&mut self) -> Vec<Result<(u64, T), FreezerError>> {
+        let batch_end = self.range.end.min(self.range.start + self.batch_size);
+        let mut stored = vec![];
+        let mut read_error = None;
+        for block_number in self.range.start..batch_end {
+            match self.table.read_stored(block_number) {
+                Ok(raw) => stored.push((block_number, raw.into_owned())),
+                Err(err) => {
+                    read_error = Some(err);
+                    break;
+                }
+            }
+        }
+        self.range = batch_end..self.range.end;
+
+        let freezer = self.table.freezer();
+        let mut batch: Vec<Result<(u64, T), FreezerError>> = self.pool.install(|| {
+            stored
+                .into_par_iter()
+                .map(|(block_number, raw)| {
+                    let item = freezer.deserialize::<T>(&freezer.decompress(&raw)?)?;
+                    Ok((block_number, item))
+                })
+                .collect()
+        });
+
+        // Items which could be read are still yielded before the error
+        if let Some(err) = read_error {
+            batch.push(Err(err));
+        }
+        batch
+    }
+}
+
+impl<'a, T: DeserializeOwned + Send> Iterator for ParFreezerIter<'a, T> {
+    type Item = Result<(u64, T), FreezerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.batch.len() == 0 {
+            if self.range.start >= self.range.end {
+                return None;
+            }
+            self.batch = self.next_batch().into_iter();
+        }
+
+        let item = self.batch.next()?;
+        if item.is_err() {
+            self.range = self.range.end..self.range.end;
+            self.batch = vec![].into_iter();
+        }
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::Freezer;
+    use crate::types::BlockBody;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parallel_iter_range() {
+        let ancient_folder = PathBuf::from("./fixtures/bodies");
+        let mut table = FreezerTable::open(&ancient_folder, Freezer::Bodies).unwrap();
+
+        let sequential = table
+            .iter_range::<BlockBody>(45000..47000)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let parallel = table
+            .par_iter_range::<BlockBody>(45000..47000, 4)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(sequential, parallel);
+
+        let mut iter = table.par_iter_range::<BlockBody>(49998..50000, 2).unwrap();
+        assert!(iter.next().unwrap().is_ok());
+        assert!(matches!(
+            iter.next(),
+            Some(Err(FreezerError::OutOfBounds(49999, 49999)))
+        ));
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::extract::{Freezer, FreezerError};
+use crate::table::{FreezerTable, ReadBackend};
+use crate::types::{Block, BlockBody, Receipts};
+use std::ops::Range;
+use std::path::Path;
+
+/// Reads complete blocks by joining all freezer tables
+///
+/// Every block is assembled from the `headers`, `hashes`, `diffs`, `bodies` and `receipts`
+/// tables, see [`Block`].
+#[derive(Debug)]
+pub struct BlockReader {
+    headers: FreezerTable,
+    hashes: FreezerTable,
+    difficulties: FreezerTable,
+    bodies: FreezerTable,
+    receipts: FreezerTable,
+}
+
+impl BlockReader {
+    /// Opens all tables of the freezer
+    pub fn open(ancient_folder: &Path) -> Result<Self, FreezerError> {
+        Self::open_with_backend(ancient_folder, ReadBackend::default())
+    }
+
+    /// Opens all tables of the freezer with the given read backend
+    pub fn open_with_backend(
+        ancient_folder: &Path,
+        backend: ReadBackend,
+    ) -> Result<Self, FreezerError> {
+        let open = |freezer| FreezerTable::open_with_backend(ancient_folder, freezer, backend);
+        Ok(BlockReader {
+            headers: open(Freezer::Headers)?,
+            hashes: open(Freezer::Hashes)?,
+            difficulties: open(Freezer::Difficulty)?,
+            bodies: open(Freezer::Bodies)?,
+            receipts: open(Freezer::Receipts)?,
+        })
+    }
+
+    /// The number of blocks which are present in all tables
+    pub fn len(&self) -> u64 {
+        [
+            &self.headers,
+            &self.hashes,
+            &self.difficulties,
+            &self.bodies,
+            &self.receipts,
+        ]
+        .iter()
+        .map(|table| table.len())
+        .min()
+        .unwrap_or(0)
+    }
+
+    /// Returns true if no complete block is available
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads a single block from all tables
+    ///
+    /// The receipts are annotated with the types of their transactions.
+    pub fn get(&mut self, block_number: u64) -> Result<Block, FreezerError> {
+        let body: BlockBody = self.bodies.get(block_number)?;
+        let mut receipts: Receipts = self.receipts.get(block_number)?;
+        receipts.set_transaction_types(&body.transactions.0);
+        Ok(Block {
+            hash: self.hashes.get(block_number)?,
+            total_difficulty: self.difficulties.get(block_number)?,
+            header: self.headers.get(block_number)?,
+            body,
+            receipts,
+        })
+    }
+
+    /// Returns an iterator over a range of blocks
+    ///
+    /// The iterator yields the block number together with the block. It stops after the first
+    /// error.
+    pub fn iter_range(&mut self, range: Range<u64>) -> BlockIter<'_> {
+        BlockIter {
+            reader: self,
+            range,
+        }
+    }
+}
+
+/// Iterator over a range of blocks of a [`BlockReader`]
+#[derive(Debug)]
+pub struct BlockIter<'a> {
+    reader: &'a mut BlockReader,
+    range: Range<u64>,
+}
+
+impl<'a> Iterator for BlockIter<'a> {
+    type Item = Result<(u64, Block), FreezerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block_number = self.range.next()?;
+        let block = self.reader.get(block_number);
+        if block.is_err() {
+            self.range = self.range.end..self.range.end;
+        }
+        Some(block.map(|block| (block_number, block)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BlockBody, BlockHash, BlockHeader, TotalDifficulty};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_reader_join_tables() {
+        // The full fixture contains all tables for blocks 0-2
+        let ancient_folder = PathBuf::from("./fixtures/full");
+        let mut reader = BlockReader::open(&ancient_folder).unwrap();
+        assert_eq!(reader.len(), 3);
+
+        let blocks = reader
+            .iter_range(0..3)
+            .collect::<Result<Vec<(u64, Block)>, FreezerError>>()
+            .unwrap();
+        assert_eq!(blocks.len(), 3);
+
+        let mut headers = FreezerTable::open(&ancient_folder, Freezer::Headers).unwrap();
+        let mut hashes = FreezerTable::open(&ancient_folder, Freezer::Hashes).unwrap();
+        let mut difficulties = FreezerTable::open(&ancient_folder, Freezer::Difficulty).unwrap();
+        let mut bodies = FreezerTable::open(&ancient_folder, Freezer::Bodies).unwrap();
+        for (block_number, block) in blocks {
+            assert_eq!(
+                block.header,
+                headers.get::<BlockHeader>(block_number).unwrap()
+            );
+            assert_eq!(block.hash, hashes.get::<BlockHash>(block_number).unwrap());
+            assert_eq!(
+                block.total_difficulty,
+                difficulties.get::<TotalDifficulty>(block_number).unwrap()
+            );
+            assert_eq!(block.body, bodies.get::<BlockBody>(block_number).unwrap());
+        }
+
+        let block = reader.get(1).unwrap();
+        assert_eq!(
+            block.hash.to_string(),
+            "\"0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6\""
+        );
+        assert_eq!(block.total_difficulty.to_string(), "34351349760");
+        assert!(matches!(
+            reader.get(3),
+            Err(FreezerError::OutOfBounds(3, 3))
+        ));
+    }
+}
@@ -1,351 +1,435 @@
 use crate::numeric::{u32_from_bytes_end_be_padded, u64_from_bytes_end_be_padded, NumericError};
 use log::trace;
-use serde::de::SeqAccess;
-use serde::Deserializer;
+use serde::de::{
+    DeserializeSeed, EnumAccess, Error as _, IntoDeserializer, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor,
+};
+use serde::{Deserialize, Deserializer};
 use thiserror::Error;
 
 mod parse;
+mod ser;
 use parse::{parse, Rlp};
+pub(crate) use ser::{encode_bytes, encode_list};
+pub use ser::{to_bytes, ListSerializer, RlpSerializer};
+
+/// Decodes a value from RLP bytes and makes sure that all bytes have been used
+pub fn from_bytes<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T, RlpError> {
+    let mut deserializer = RlpDeserializer::new(bytes)?;
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
 
 /// A deserializer used to convert from RLP bytes into the different types
+///
+/// Every call consumes one RLP item. Strings can be deserialized as integers, booleans, text,
+/// bytes, sequences or tuples of `u8`. Lists can be deserialized as sequences, tuples and
+/// structs. The encoding follows [`RlpSerializer`]:
+/// - `None`, `false` and unit are the empty string, `Some` is the value itself
+/// - maps are lists of key-value lists
+/// - unit variants are their variant index, other variants are lists starting with the index
+///
+/// Signed integers and floats have no RLP representation and return an error.
 #[derive(Debug)]
-pub(crate) struct RlpDeserializer<'de> {
-    parsed: Vec<Rlp<'de>>,
-    rest: &'de [u8],
+pub struct RlpDeserializer<'de> {
+    input: &'de [u8],
 }
 
 impl<'de> RlpDeserializer<'de> {
     /// Create a new rlp deserializer from some byte slice
-    pub(crate) fn new(bytes: &'de [u8]) -> Result<RlpDeserializer, RlpError> {
+    pub fn new(bytes: &'de [u8]) -> Result<RlpDeserializer<'de>, RlpError> {
         trace!("Creating new rlp deserializer for {:?}", &bytes);
-        let rlp_deserializer = RlpDeserializer {
-            parsed: vec![],
-            rest: bytes,
-        };
-        Ok(rlp_deserializer)
+        Ok(RlpDeserializer { input: bytes })
     }
 
-    fn parse(&mut self) -> Result<(), RlpError> {
-        trace!(
-            "Parsing Rlp: Parsed: {:?}\n Unparsed: {:?}",
-            self.parsed,
-            self.rest
-        );
-        if let Some(Rlp::List(inner)) = self.parsed.last_mut() {
-            let (parsed, slice) = parse(inner)?;
-            *inner = slice;
-            self.parsed.push(parsed);
-            return Ok(());
+    /// Returns an error if there is input left
+    pub fn end(&self) -> Result<(), RlpError> {
+        match self.input.len() {
+            0 => Ok(()),
+            len => Err(RlpError::TrailingBytes(len)),
         }
-        let (parsed, slice) = parse(self.rest)?;
-        self.parsed.push(parsed);
-        self.rest = slice;
-        Ok(())
+    }
+
+    fn peek(&self) -> Result<Rlp<'de>, RlpError> {
+        parse(self.input).map(|(rlp, _)| rlp)
+    }
+
+    fn next_item(&mut self) -> Result<Rlp<'de>, RlpError> {
+        let (rlp, rest) = parse(self.input)?;
+        trace!("Parsed {:?}", rlp);
+        self.input = rest;
+        Ok(rlp)
+    }
+
+    fn next_bytes(&mut self) -> Result<&'de [u8], RlpError> {
+        match self.next_item()? {
+            Rlp::Bytes(bytes) => Ok(bytes),
+            Rlp::Empty => Ok(&[]),
+            Rlp::List(_) | Rlp::EmptyList => Err(RlpError::ExpectedBytes),
+        }
+    }
+
+    fn next_list(&mut self) -> Result<RlpDeserializer<'de>, RlpError> {
+        match self.next_item()? {
+            Rlp::List(inner) => Ok(RlpDeserializer { input: inner }),
+            Rlp::EmptyList => Ok(RlpDeserializer { input: &[] }),
+            Rlp::Bytes(_) | Rlp::Empty => Err(RlpError::ExpectedList),
+        }
+    }
+
+    fn next_u32(&mut self, max_len: usize, name: &'static str) -> Result<u32, RlpError> {
+        let bytes = self.next_bytes()?;
+        if bytes.len() > max_len {
+            return Err(RlpError::Overflow(bytes.len(), name));
+        }
+        u32_from_bytes_end_be_padded(bytes).map_err(RlpError::Conversion)
     }
 }
 
-impl<'de: 'a, 'a> Deserializer<'de> for &'a mut RlpDeserializer<'de> {
+impl<'de> Deserializer<'de> for &mut RlpDeserializer<'de> {
     type Error = RlpError;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn is_human_readable(&self) -> bool {
+        false
     }
 
-    fn deserialize_bool<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        match self.peek()? {
+            Rlp::Bytes(_) | Rlp::Empty => self.deserialize_bytes(visitor),
+            Rlp::List(_) | Rlp::EmptyList => self.deserialize_seq(visitor),
+        }
     }
 
-    fn deserialize_i8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        match self.next_bytes()? {
+            [] => visitor.visit_bool(false),
+            [0x01] => visitor.visit_bool(true),
+            bytes => Err(RlpError::invalid_value(
+                Unexpected::Bytes(bytes),
+                &"an empty string or 0x01",
+            )),
+        }
     }
 
-    fn deserialize_i16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_i8<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, RlpError> {
+        Err(RlpError::Unsupported("i8"))
     }
 
-    fn deserialize_i32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_i16<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, RlpError> {
+        Err(RlpError::Unsupported("i16"))
     }
 
-    fn deserialize_i64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_i32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, RlpError> {
+        Err(RlpError::Unsupported("i32"))
     }
 
-    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.parsed.last_mut() {
-            Some(Rlp::Bytes(bytes)) => {
-                let byte = bytes[0];
-                *bytes = &bytes[1..];
-                visitor.visit_u8(byte)
-            }
-            Some(empty @ Rlp::Empty) => {
-                *empty = Rlp::Bytes(&[]);
-                visitor.visit_u8(0)
-            }
-            _ => Err(RlpError::UnexpectedMatch),
-        }
+    fn deserialize_i64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, RlpError> {
+        Err(RlpError::Unsupported("i64"))
     }
 
-    fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        visitor.visit_u8(self.next_u32(1, "u8")? as u8)
     }
 
-    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.parsed.last_mut() {
-            Some(Rlp::Bytes(bytes)) => {
-                let new_u32 = u32_from_bytes_end_be_padded(bytes).map_err(RlpError::Conversion)?;
-                *bytes = &bytes[..bytes.len().saturating_sub(4)];
-                visitor.visit_u32(new_u32)
-            }
-            Some(empty @ Rlp::Empty) => {
-                *empty = Rlp::Bytes(&[]);
-                visitor.visit_u32(0)
-            }
-            _ => Err(RlpError::UnexpectedMatch),
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        visitor.visit_u16(self.next_u32(2, "u16")? as u16)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        visitor.visit_u32(self.next_u32(4, "u32")?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        let bytes = self.next_bytes()?;
+        if bytes.len() > 8 {
+            return Err(RlpError::Overflow(bytes.len(), "u64"));
         }
+        visitor.visit_u64(u64_from_bytes_end_be_padded(bytes).map_err(RlpError::Conversion)?)
     }
 
-    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        match self.parsed.last_mut() {
-            Some(Rlp::Bytes(bytes)) => {
-                let new_u64 = u64_from_bytes_end_be_padded(bytes).map_err(RlpError::Conversion)?;
-                *bytes = &bytes[..bytes.len().saturating_sub(8)];
-                visitor.visit_u64(new_u64)
-            }
-            Some(empty @ Rlp::Empty) => {
-                *empty = Rlp::Bytes(&[]);
-                visitor.visit_u64(0)
-            }
-            _ => Err(RlpError::UnexpectedMatch),
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        let bytes = self.next_bytes()?;
+        if bytes.len() > 16 {
+            return Err(RlpError::Overflow(bytes.len(), "u128"));
         }
+        visitor.visit_u128(
+            bytes
+                .iter()
+                .fold(0, |value, &byte| (value << 8) | byte as u128),
+        )
     }
 
-    fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_f32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, RlpError> {
+        Err(RlpError::Unsupported("f32"))
     }
 
-    fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_f64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, RlpError> {
+        Err(RlpError::Unsupported("f64"))
     }
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        self.deserialize_str(visitor)
     }
 
-    fn deserialize_str<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        let bytes = self.next_bytes()?;
+        visitor.visit_borrowed_str(std::str::from_utf8(bytes).map_err(RlpError::Utf8)?)
     }
 
-    fn deserialize_string<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        self.deserialize_str(visitor)
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        visitor.visit_borrowed_bytes(self.next_bytes()?)
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        self.deserialize_bytes(visitor)
     }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        match self.peek()? {
+            Rlp::Empty => {
+                let _ = self.next_item()?;
+                visitor.visit_none()
+            }
+            _ => visitor.visit_some(self),
+        }
     }
 
-    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        match self.next_bytes()? {
+            [] => visitor.visit_unit(),
+            bytes => Err(RlpError::invalid_value(
+                Unexpected::Bytes(bytes),
+                &"an empty string",
+            )),
+        }
     }
 
-    fn deserialize_unit_struct<V>(
+    fn deserialize_unit_struct<V: Visitor<'de>>(
         self,
         _name: &'static str,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+        visitor: V,
+    ) -> Result<V::Value, RlpError> {
+        self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
         self,
         _name: &'static str,
         visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        self.parse()?;
-        visitor.visit_seq(SeqAccessor {
-            de: self,
-            iterate: false,
-        })
-    }
-
-    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        visitor.visit_seq(SeqAccessor {
-            de: self,
-            iterate: false,
-        })
+    ) -> Result<V::Value, RlpError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        match self.next_item()? {
+            Rlp::Bytes(bytes) => visitor.visit_seq(ByteAccessor { bytes }),
+            Rlp::Empty => visitor.visit_seq(ByteAccessor { bytes: &[] }),
+            Rlp::List(inner) => visitor.visit_seq(ListAccessor {
+                de: RlpDeserializer { input: inner },
+            }),
+            Rlp::EmptyList => visitor.visit_seq(ListAccessor {
+                de: RlpDeserializer { input: &[] },
+            }),
+        }
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        visitor.visit_seq(SeqAccessor {
-            de: self,
-            iterate: false,
-        })
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, RlpError> {
+        self.deserialize_seq(visitor)
     }
 
-    fn deserialize_tuple_struct<V>(
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
         self,
         _name: &'static str,
         _len: usize,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+        visitor: V,
+    ) -> Result<V::Value, RlpError> {
+        visitor.visit_seq(ListAccessor {
+            de: self.next_list()?,
+        })
     }
 
-    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        visitor.visit_map(MapAccessor {
+            de: self.next_list()?,
+            entry: None,
+        })
     }
 
-    fn deserialize_struct<V>(
+    fn deserialize_struct<V: Visitor<'de>>(
         self,
         _name: &'static str,
         _fields: &'static [&'static str],
         visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        self.parse()?;
-        visitor.visit_seq(SeqAccessor {
-            de: self,
-            iterate: true,
+    ) -> Result<V::Value, RlpError> {
+        visitor.visit_seq(ListAccessor {
+            de: self.next_list()?,
         })
     }
 
-    fn deserialize_enum<V>(
+    fn deserialize_enum<V: Visitor<'de>>(
         self,
         _name: &'static str,
         _variants: &'static [&'static str],
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+        visitor: V,
+    ) -> Result<V::Value, RlpError> {
+        match self.peek()? {
+            Rlp::Bytes(_) | Rlp::Empty => visitor.visit_enum(VariantAccessor {
+                index: self.next_u32(4, "u32")?,
+                fields: None,
+            }),
+            Rlp::List(_) | Rlp::EmptyList => {
+                let mut fields = self.next_list()?;
+                visitor.visit_enum(VariantAccessor {
+                    index: fields.next_u32(4, "u32")?,
+                    fields: Some(fields),
+                })
+            }
+        }
     }
 
-    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    // Identifiers are only used for enum variants, which are encoded with their index
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        self.deserialize_u32(visitor)
     }
 
-    fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        unimplemented!()
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RlpError> {
+        let _ = self.next_item()?;
+        visitor.visit_unit()
     }
 }
 
-struct SeqAccessor<'a, 'de: 'a> {
-    de: &'a mut RlpDeserializer<'de>,
-    iterate: bool,
+// Hands out the elements of an RLP list
+struct ListAccessor<'de> {
+    de: RlpDeserializer<'de>,
 }
 
-impl<'de: 'a, 'a> SeqAccess<'de> for SeqAccessor<'a, 'de> {
+impl<'de> SeqAccess<'de> for ListAccessor<'de> {
     type Error = RlpError;
 
-    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
-    where
-        T: serde::de::DeserializeSeed<'de>,
-    {
-        if let Some(Rlp::List(&[])) = self.de.parsed.last() {
-            self.de.parsed.pop().ok_or(RlpError::NoInputLeft)?;
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, RlpError> {
+        if self.de.input.is_empty() {
+            return Ok(None);
         }
-        if let Some(Rlp::List(&[]) | Rlp::Bytes(&[]) | Rlp::EmptyList) = self.de.parsed.last() {
-            self.de.parsed.pop().ok_or(RlpError::NoInputLeft)?;
-            if !self.iterate {
-                return Ok(None);
+        seed.deserialize(&mut self.de).map(Some)
+    }
+}
+
+// Hands out the bytes of an RLP string
+struct ByteAccessor<'de> {
+    bytes: &'de [u8],
+}
+
+impl<'de> SeqAccess<'de> for ByteAccessor<'de> {
+    type Error = RlpError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, RlpError> {
+        match self.bytes.split_first() {
+            Some((&byte, rest)) => {
+                self.bytes = rest;
+                seed.deserialize(byte.into_deserializer()).map(Some)
             }
+            None => Ok(None),
         }
-        if self.iterate {
-            self.de.parse()?;
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.bytes.len())
+    }
+}
+
+// Hands out the entries of a list of key-value lists
+struct MapAccessor<'de> {
+    de: RlpDeserializer<'de>,
+    entry: Option<RlpDeserializer<'de>>,
+}
+
+impl<'de> MapAccess<'de> for MapAccessor<'de> {
+    type Error = RlpError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, RlpError> {
+        if self.de.input.is_empty() {
+            return Ok(None);
+        }
+        let mut entry = self.de.next_list()?;
+        let key = seed.deserialize(&mut entry)?;
+        self.entry = Some(entry);
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, RlpError> {
+        let mut entry = self.entry.take().ok_or(RlpError::NoInputLeft)?;
+        let value = seed.deserialize(&mut entry)?;
+        entry.end()?;
+        Ok(value)
+    }
+}
+
+// An enum variant given by its index and, unless it is a unit variant, a list of fields
+struct VariantAccessor<'de> {
+    index: u32,
+    fields: Option<RlpDeserializer<'de>>,
+}
+
+impl<'de> EnumAccess<'de> for VariantAccessor<'de> {
+    type Error = RlpError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), RlpError> {
+        let variant = seed.deserialize(self.index.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantAccessor<'de> {
+    type Error = RlpError;
+
+    fn unit_variant(self) -> Result<(), RlpError> {
+        match self.fields {
+            Some(fields) => fields.end(),
+            None => Ok(()),
         }
-        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, RlpError> {
+        let mut fields = self.fields.ok_or(RlpError::ExpectedList)?;
+        let value = seed.deserialize(&mut fields)?;
+        fields.end()?;
+        Ok(value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, RlpError> {
+        visitor.visit_seq(ListAccessor {
+            de: self.fields.ok_or(RlpError::ExpectedList)?,
+        })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, RlpError> {
+        self.tuple_variant(0, visitor)
     }
 }
 
@@ -358,8 +442,20 @@ pub enum RlpError {
     NoInputLeft,
     #[error("Unexpected match")]
     UnexpectedMatch,
+    #[error("Expected an RLP string but found a list")]
+    ExpectedBytes,
+    #[error("Expected an RLP list but found a string")]
+    ExpectedList,
+    #[error("{0} bytes are left after decoding")]
+    TrailingBytes(usize),
     #[error("Type conversion error: {0}")]
     Conversion(#[source] NumericError),
+    #[error("An integer of {0} bytes does not fit into {1}")]
+    Overflow(usize, &'static str),
+    #[error("Invalid UTF-8 string: {0}")]
+    Utf8(#[source] std::str::Utf8Error),
+    #[error("Type {0} is not supported by RLP")]
+    Unsupported(&'static str),
     #[error("Error during RLP deserialization: {0}")]
     CustomError(String),
 }
@@ -372,3 +468,141 @@ impl serde::de::Error for RlpError {
         RlpError::CustomError(msg.to_string())
     }
 }
+
+impl serde::ser::Error for RlpError {
+    fn custom<T>(msg: T) -> RlpError
+    where
+        T: std::fmt::Display,
+    {
+        RlpError::CustomError(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    enum Shape {
+        Point,
+        Circle(u64),
+        Line(u8, u8),
+        Rect { width: u16, height: u16 },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Wrapper(u32, String);
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Everything {
+        flag: bool,
+        small: u16,
+        big: u128,
+        text: String,
+        letter: char,
+        missing: Option<u64>,
+        present: Option<u64>,
+        nothing: (),
+        wrapper: Wrapper,
+        map: BTreeMap<u8, String>,
+        shapes: Vec<Shape>,
+    }
+
+    #[test]
+    fn test_deserialize_data_model() {
+        let everything = Everything {
+            flag: true,
+            small: 0x1234,
+            big: u128::MAX,
+            text: String::from("dog"),
+            letter: 'ß',
+            missing: None,
+            present: Some(1024),
+            nothing: (),
+            wrapper: Wrapper(7, String::new()),
+            map: BTreeMap::from([(1, String::from("a")), (2, String::from("b"))]),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(3),
+                Shape::Line(1, 2),
+                Shape::Rect {
+                    width: 640,
+                    height: 480,
+                },
+            ],
+        };
+        let bytes = to_bytes(&everything).unwrap();
+        assert_eq!(from_bytes::<Everything>(&bytes).unwrap(), everything);
+
+        // Unit variants are their index, other variants a list starting with the index
+        assert_eq!(from_bytes::<Shape>(&[0x80]).unwrap(), Shape::Point);
+        assert_eq!(
+            from_bytes::<Shape>(&[0xc2, 0x01, 0x03]).unwrap(),
+            Shape::Circle(3)
+        );
+        assert_eq!(
+            from_bytes::<&[u8]>(&[0x82, 0xab, 0xcd]).unwrap(),
+            &[0xab, 0xcd]
+        );
+        assert_eq!(from_bytes::<Vec<u8>>(&[0x80]).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_deserialize_ignored_any() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct First {
+            first: u8,
+        }
+
+        // Trailing list elements are skipped
+        let bytes = to_bytes(&(1_u8, vec!["ignored"], 3_u8)).unwrap();
+        assert_eq!(from_bytes::<First>(&bytes).unwrap(), First { first: 1 });
+        assert!(matches!(
+            from_bytes::<serde::de::IgnoredAny>(&[0x01, 0x02]),
+            Err(RlpError::TrailingBytes(1))
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        assert!(matches!(
+            from_bytes::<i64>(&[0x01]),
+            Err(RlpError::Unsupported("i64"))
+        ));
+        assert!(matches!(
+            from_bytes::<f64>(&[0x01]),
+            Err(RlpError::Unsupported("f64"))
+        ));
+        assert!(matches!(
+            from_bytes::<u8>(&[0x82, 0x01, 0x00]),
+            Err(RlpError::Overflow(2, "u8"))
+        ));
+        assert!(matches!(
+            from_bytes::<u64>(&[0xc0]),
+            Err(RlpError::ExpectedBytes)
+        ));
+        assert!(matches!(
+            from_bytes::<Wrapper>(&[0x01]),
+            Err(RlpError::ExpectedList)
+        ));
+        assert!(matches!(
+            from_bytes::<String>(&[0x81, 0xff]),
+            Err(RlpError::Utf8(_))
+        ));
+        assert!(matches!(
+            from_bytes::<bool>(&[0x02]),
+            Err(RlpError::CustomError(_))
+        ));
+        assert!(matches!(
+            from_bytes::<Shape>(&[0x04]),
+            Err(RlpError::CustomError(_))
+        ));
+        assert!(matches!(
+            from_bytes::<Wrapper>(&[0xc1, 0x07]),
+            Err(RlpError::CustomError(_))
+        ));
+        assert!(matches!(from_bytes::<u8>(&[]), Err(RlpError::NoInputLeft)));
+    }
+}
@@ -23,7 +23,7 @@ impl<'a> std::fmt::Debug for Rlp<'a> {
 }
 
 /// Parse the first rlp match of a slice
-pub(crate) fn parse(rlp_slice: &[u8]) -> Result<(Rlp, &[u8]), RlpError> {
+pub(crate) fn parse(rlp_slice: &[u8]) -> Result<(Rlp<'_>, &[u8]), RlpError> {
     let len = rlp_slice.len();
     trace!("Parsing slice of length {}: {:?}", len, &rlp_slice);
 
@@ -51,7 +51,7 @@ pub(crate) fn parse(rlp_slice: &[u8]) -> Result<(Rlp, &[u8]), RlpError> {
     Err(RlpError::NoMatch)
 }
 
-fn match_empty(rlp_slice: &[u8]) -> (Option<Rlp>, &[u8]) {
+fn match_empty(rlp_slice: &[u8]) -> (Option<Rlp<'_>>, &[u8]) {
     if rlp_slice[0] == 0xc0 {
         (Some(Rlp::EmptyList), &rlp_slice[1..])
     } else if rlp_slice[0] == 0x80 {
@@ -61,7 +61,7 @@ fn match_empty(rlp_slice: &[u8]) -> (Option<Rlp>, &[u8]) {
     }
 }
 
-fn match_byte(rlp_slice: &[u8]) -> (Option<Rlp>, &[u8]) {
+fn match_byte(rlp_slice: &[u8]) -> (Option<Rlp<'_>>, &[u8]) {
     if rlp_slice[0] <= 0x7f {
         (Some(Rlp::Bytes(&rlp_slice[..1])), &rlp_slice[1..])
     } else {
@@ -69,84 +69,66 @@ fn match_byte(rlp_slice: &[u8]) -> (Option<Rlp>, &[u8]) {
     }
 }
 
-fn match_short_str(rlp_slice: &[u8], len: usize) -> (Option<Rlp>, &[u8]) {
-    if rlp_slice[0] <= 0xb7 && len > (rlp_slice[0] - 0x80) as usize {
-        (
-            Some(Rlp::Bytes(&rlp_slice[1..(rlp_slice[0] - 0x7f) as usize])),
-            &rlp_slice[(rlp_slice[0] - 0x7f) as usize..],
-        )
-    } else {
-        (None, rlp_slice)
+fn match_short_str(rlp_slice: &[u8], len: usize) -> (Option<Rlp<'_>>, &[u8]) {
+    match rlp_slice[0] {
+        prefix @ 0x81..=0xb7 if len > (prefix - 0x80) as usize => {
+            let end = (prefix - 0x7f) as usize;
+            (Some(Rlp::Bytes(&rlp_slice[1..end])), &rlp_slice[end..])
+        }
+        _ => (None, rlp_slice),
     }
 }
 
-fn match_long_str(rlp_slice: &[u8], len: usize) -> Result<(Option<Rlp>, &[u8]), RlpError> {
-    if rlp_slice[0] <= 0xbf
-        && len > (rlp_slice[0] - 0xb7) as usize
-        && len
-            > (rlp_slice[0] - 0xb7) as usize
-                + usize_from_bytes_be_padded(&rlp_slice[1..(rlp_slice[0] - 0xb6) as usize])
-                    .map_err(RlpError::Conversion)?
-    {
-        Ok((
-            Some(Rlp::Bytes(
-                &rlp_slice[(rlp_slice[0] - 0xb6) as usize
-                    ..usize_from_bytes_be_padded(&rlp_slice[1..(rlp_slice[0] - 0xb6) as usize])
-                        .map_err(RlpError::Conversion)?
-                        + (rlp_slice[0] - 0xb6) as usize],
-            )),
-            &rlp_slice[usize_from_bytes_be_padded(&rlp_slice[1..(rlp_slice[0] - 0xb6) as usize])
-                .map_err(RlpError::Conversion)?
-                + (rlp_slice[0] - 0xb6) as usize..],
-        ))
-    } else {
-        Ok((None, rlp_slice))
+fn match_long_str(rlp_slice: &[u8], len: usize) -> Result<(Option<Rlp<'_>>, &[u8]), RlpError> {
+    match rlp_slice[0] {
+        prefix @ 0xb8..=0xbf => {
+            let (payload, rest) = split_long_payload(rlp_slice, len, (prefix - 0xb7) as usize)?;
+            Ok((Some(Rlp::Bytes(payload)), rest))
+        }
+        _ => Ok((None, rlp_slice)),
+    }
+}
+
+fn match_short_list(rlp_slice: &[u8], len: usize) -> Result<(Option<Rlp<'_>>, &[u8]), RlpError> {
+    match rlp_slice[0] {
+        prefix @ 0xc1..=0xf7 if len > (prefix - 0xc0) as usize => {
+            let end = (prefix - 0xbf) as usize;
+            Ok((Some(Rlp::List(&rlp_slice[1..end])), &rlp_slice[end..]))
+        }
+        _ => Ok((None, rlp_slice)),
     }
 }
 
-fn match_short_list(rlp_slice: &[u8], len: usize) -> Result<(Option<Rlp>, &[u8]), RlpError> {
-    Ok(
-        if rlp_slice[0] <= 0xf7 && len > (rlp_slice[0] - 0xc0) as usize {
-            (
-                Some(Rlp::List(&rlp_slice[1..(rlp_slice[0] - 0xbf) as usize])),
-                &rlp_slice[(rlp_slice[0] - 0xbf) as usize..],
-            )
-        } else {
-            (None, rlp_slice)
-        },
-    )
+fn match_long_list(rlp_slice: &[u8], len: usize) -> Result<(Option<Rlp<'_>>, &[u8]), RlpError> {
+    match rlp_slice[0] {
+        prefix @ 0xf8..=0xff => {
+            let (payload, rest) = split_long_payload(rlp_slice, len, (prefix - 0xf7) as usize)?;
+            Ok((Some(Rlp::List(payload)), rest))
+        }
+        _ => Ok((None, rlp_slice)),
+    }
 }
 
-fn match_long_list(rlp_slice: &[u8], len: usize) -> Result<(Option<Rlp>, &[u8]), RlpError> {
-    Ok(
-        if len > (rlp_slice[0] - 0xf7) as usize
-            && len
-                > (rlp_slice[0] - 0xf7) as usize
-                    + usize_from_bytes_be_padded(&rlp_slice[1..(rlp_slice[0] - 0xf6) as usize])
-                        .map_err(RlpError::Conversion)?
-        {
-            (
-                Some(Rlp::List(
-                    &rlp_slice[(rlp_slice[0] - 0xf6) as usize
-                        ..usize_from_bytes_be_padded(
-                            &rlp_slice[1..(rlp_slice[0] - 0xf6) as usize],
-                        )
-                        .map_err(RlpError::Conversion)?
-                            + (rlp_slice[0] - 0xf6) as usize],
-                )),
-                &rlp_slice[usize_from_bytes_be_padded(
-                    &rlp_slice[1..(rlp_slice[0] - 0xf6) as usize],
-                )
-                .map_err(RlpError::Conversion)?
-                    + (rlp_slice[0] - 0xf6) as usize..],
-            )
-        } else {
-            (None, rlp_slice)
-        },
-    )
+// Splits off the payload of a long string or list whose length is encoded in `len_of_len` bytes
+fn split_long_payload(
+    rlp_slice: &[u8],
+    len: usize,
+    len_of_len: usize,
+) -> Result<(&[u8], &[u8]), RlpError> {
+    let start = 1 + len_of_len;
+    if len < start {
+        return Err(RlpError::NoInputLeft);
+    }
+    let payload_len =
+        usize_from_bytes_be_padded(&rlp_slice[1..start]).map_err(RlpError::Conversion)?;
+    match start.checked_add(payload_len) {
+        Some(end) if end <= len => Ok((&rlp_slice[start..end], &rlp_slice[end..])),
+        _ => Err(RlpError::NoInputLeft),
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec, clippy::byte_char_slices, clippy::manual_repeat_n)]
 mod tests {
     use super::*;
 
@@ -183,6 +165,21 @@ mod tests {
         let _rlp = parse(&vec[..]).unwrap();
     }
 
+    #[test]
+    fn test_rlp_parse_truncated() {
+        for truncated in [
+            &[0x85, 0x01, 0x02][..],
+            &[0xb8],
+            &[0xb9, 0x01, 0x00, 0x00],
+            &[0xbf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            &[0xc3, 0x01],
+            &[0xf8, 0x38, 0xc0],
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ] {
+            assert!(parse(truncated).is_err(), "{:x?}", truncated);
+        }
+    }
+
     #[test]
     fn test_rlp_match_empty() {
         let first = vec![0xc0_u8];
@@ -0,0 +1,462 @@
+use super::RlpError;
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::Serializer;
+
+/// Encodes a value into canonical RLP bytes
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, RlpError> {
+    let mut serializer = RlpSerializer::default();
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+/// A serializer used to convert the different types into RLP bytes
+///
+/// Integers are encoded with the minimal number of big endian bytes, `false` and `None` are
+/// encoded as the empty string. Sequences, tuples and structs become lists. Maps become a list of
+/// key-value lists and enum variants are encoded with their variant index.
+#[derive(Debug, Default)]
+pub struct RlpSerializer {
+    output: Vec<u8>,
+}
+
+impl RlpSerializer {
+    /// Returns the bytes which have been encoded so far
+    pub fn into_inner(self) -> Vec<u8> {
+        self.output
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        if let [byte @ 0x00..=0x7f] = bytes {
+            self.output.push(*byte);
+            return;
+        }
+        self.output.extend(length_prefix(bytes.len(), 0x80));
+        self.output.extend_from_slice(bytes);
+    }
+
+    fn push_uint(&mut self, value: u64) {
+        let bytes = value.to_be_bytes();
+        let leading_zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+        self.push_bytes(&bytes[leading_zeros..]);
+    }
+
+    fn begin_list(&mut self) -> ListSerializer<'_> {
+        let start = self.output.len();
+        ListSerializer {
+            ser: self,
+            start,
+            entry_start: None,
+        }
+    }
+}
+
+/// Encodes a byte string
+pub(crate) fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut serializer = RlpSerializer::default();
+    serializer.push_bytes(bytes);
+    serializer.into_inner()
+}
+
+/// Encodes a list of items which are already encoded
+pub(crate) fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut list = length_prefix(payload.len(), 0xc0);
+    list.extend(payload);
+    list
+}
+
+// Returns the prefix of a string (offset 0x80) or a list (offset 0xc0) of the given length
+fn length_prefix(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+    let len_bytes = len.to_be_bytes();
+    let leading_zeros = len_bytes.iter().take_while(|&&byte| byte == 0).count();
+    let mut prefix = vec![offset + 55 + (len_bytes.len() - leading_zeros) as u8];
+    prefix.extend_from_slice(&len_bytes[leading_zeros..]);
+    prefix
+}
+
+/// Encodes the elements of a list and prepends the list prefix once the payload is known
+#[derive(Debug)]
+pub struct ListSerializer<'a> {
+    ser: &'a mut RlpSerializer,
+    start: usize,
+    entry_start: Option<usize>,
+}
+
+impl<'a> ListSerializer<'a> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RlpError> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn finish(self) -> Result<(), RlpError> {
+        close_list(self.ser, self.start);
+        Ok(())
+    }
+}
+
+// Prepends the list prefix to everything which has been encoded since `start`
+fn close_list(ser: &mut RlpSerializer, start: usize) {
+    let prefix = length_prefix(ser.output.len() - start, 0xc0);
+    let _ = ser.output.splice(start..start, prefix);
+}
+
+impl<'a> Serializer for &'a mut RlpSerializer {
+    type Ok = ();
+    type Error = RlpError;
+    type SerializeSeq = ListSerializer<'a>;
+    type SerializeTuple = ListSerializer<'a>;
+    type SerializeTupleStruct = ListSerializer<'a>;
+    type SerializeTupleVariant = ListSerializer<'a>;
+    type SerializeMap = ListSerializer<'a>;
+    type SerializeStruct = ListSerializer<'a>;
+    type SerializeStructVariant = ListSerializer<'a>;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<(), RlpError> {
+        self.push_uint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), RlpError> {
+        Err(RlpError::Unsupported("i8"))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), RlpError> {
+        Err(RlpError::Unsupported("i16"))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), RlpError> {
+        Err(RlpError::Unsupported("i32"))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), RlpError> {
+        Err(RlpError::Unsupported("i64"))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), RlpError> {
+        self.push_uint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), RlpError> {
+        self.push_uint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), RlpError> {
+        self.push_uint(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), RlpError> {
+        self.push_uint(v);
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), RlpError> {
+        let bytes = v.to_be_bytes();
+        let leading_zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+        self.push_bytes(&bytes[leading_zeros..]);
+        Ok(())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), RlpError> {
+        Err(RlpError::Unsupported("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), RlpError> {
+        Err(RlpError::Unsupported("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), RlpError> {
+        self.push_bytes(v.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), RlpError> {
+        self.push_bytes(v.as_bytes());
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), RlpError> {
+        self.push_bytes(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), RlpError> {
+        self.push_bytes(&[]);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), RlpError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), RlpError> {
+        self.push_bytes(&[]);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), RlpError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), RlpError> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), RlpError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), RlpError> {
+        let mut list = self.begin_list();
+        list.element(&variant_index)?;
+        list.element(value)?;
+        list.finish()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ListSerializer<'a>, RlpError> {
+        Ok(self.begin_list())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<ListSerializer<'a>, RlpError> {
+        Ok(self.begin_list())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer<'a>, RlpError> {
+        Ok(self.begin_list())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer<'a>, RlpError> {
+        let mut list = self.begin_list();
+        list.element(&variant_index)?;
+        Ok(list)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<ListSerializer<'a>, RlpError> {
+        Ok(self.begin_list())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer<'a>, RlpError> {
+        Ok(self.begin_list())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer<'a>, RlpError> {
+        let mut list = self.begin_list();
+        list.element(&variant_index)?;
+        Ok(list)
+    }
+}
+
+impl<'a> SerializeSeq for ListSerializer<'a> {
+    type Ok = ();
+    type Error = RlpError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RlpError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), RlpError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeTuple for ListSerializer<'a> {
+    type Ok = ();
+    type Error = RlpError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RlpError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), RlpError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeTupleStruct for ListSerializer<'a> {
+    type Ok = ();
+    type Error = RlpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RlpError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), RlpError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeTupleVariant for ListSerializer<'a> {
+    type Ok = ();
+    type Error = RlpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RlpError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), RlpError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeMap for ListSerializer<'a> {
+    type Ok = ();
+    type Error = RlpError;
+
+    // Every entry is encoded as a list of key and value
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), RlpError> {
+        self.entry_start = Some(self.ser.output.len());
+        self.element(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RlpError> {
+        let entry_start = self.entry_start.take().ok_or(RlpError::UnexpectedMatch)?;
+        self.element(value)?;
+        close_list(self.ser, entry_start);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), RlpError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeStruct for ListSerializer<'a> {
+    type Ok = ();
+    type Error = RlpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), RlpError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), RlpError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeStructVariant for ListSerializer<'a> {
+    type Ok = ();
+    type Error = RlpError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), RlpError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), RlpError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_serialize_integers() {
+        assert_eq!(to_bytes(&0_u64).unwrap(), vec![0x80]);
+        assert_eq!(to_bytes(&15_u8).unwrap(), vec![0x0f]);
+        assert_eq!(to_bytes(&0x80_u16).unwrap(), vec![0x81, 0x80]);
+        assert_eq!(to_bytes(&1024_u32).unwrap(), vec![0x82, 0x04, 0x00]);
+        assert_eq!(
+            to_bytes(&u64::MAX).unwrap(),
+            vec![0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(to_bytes(&true).unwrap(), vec![0x01]);
+        assert_eq!(to_bytes(&false).unwrap(), vec![0x80]);
+        assert!(matches!(to_bytes(&-1_i64), Err(RlpError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_serialize_strings() {
+        assert_eq!(to_bytes("").unwrap(), vec![0x80]);
+        assert_eq!(to_bytes("dog").unwrap(), vec![0x83, b'd', b'o', b'g']);
+        assert_eq!(to_bytes(&None::<u64>).unwrap(), vec![0x80]);
+        assert_eq!(to_bytes(&Some(1_u64)).unwrap(), vec![0x01]);
+
+        let long = "Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let mut expected = vec![0xb8, 0x38];
+        expected.extend_from_slice(long.as_bytes());
+        assert_eq!(to_bytes(long).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_serialize_lists() {
+        assert_eq!(to_bytes(&Vec::<u64>::new()).unwrap(), vec![0xc0]);
+        assert_eq!(
+            to_bytes(&["cat", "dog"]).unwrap(),
+            vec![0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g']
+        );
+        // The set theoretical representation of three
+        type Zero = Vec<()>;
+        let three: (Zero, (Zero,), (Zero, (Zero,))) = (vec![], (vec![],), (vec![], (vec![],)));
+        assert_eq!(
+            to_bytes(&three).unwrap(),
+            vec![0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0]
+        );
+
+        let long = vec![1_u8; 60];
+        let mut expected = vec![0xf8, 60];
+        expected.extend_from_slice(&long);
+        assert_eq!(to_bytes(&long).unwrap(), expected);
+
+        let map = BTreeMap::from([(1_u8, "a"), (2_u8, "b")]);
+        assert_eq!(
+            to_bytes(&map).unwrap(),
+            vec![0xc6, 0xc2, 0x01, 0x61, 0xc2, 0x02, 0x62]
+        );
+    }
+}
@@ -0,0 +1,690 @@
+use crate::extract::{Freezer, FreezerError, IndexEntry, ItemSpan, INDEX_ENTRY_SIZE};
+use crate::rlp::RlpDeserializer;
+use log::{debug, info, trace};
+use memmap2::Mmap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A handle to a single table of the freezer
+///
+/// In contrast to [`Freezer::init`] this does not load the index or the data files into memory,
+/// but reads every item by its own index entries. Memory usage is bounded by the size of a single
+/// item, regardless of how many blocks are read.
+///
+/// Both the legacy layout and the layout of newer geth versions are supported. The latter stores
+/// the tables in `ancient/chain`, writes a `*.meta` file for every table and may have deleted
+/// items from the tail of the table.
+#[derive(Debug)]
+pub struct FreezerTable {
+    freezer: Freezer,
+    ancient_folder: PathBuf,
+    backend: ReadBackend,
+    index_file: File,
+    index_map: Option<Mmap>,
+    meta: Option<TableMeta>,
+    tail: TableTail,
+    items: u64,
+    data_file: Option<(u16, DataFile)>,
+}
+
+/// Decides how a [`FreezerTable`] reads its index and data files
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ReadBackend {
+    /// Seeks and reads into a freshly allocated buffer for every item
+    #[default]
+    File,
+    /// Memory-maps the files and reads items without syscalls or copies
+    ///
+    /// The files must not be truncated while they are mapped, so geth should not be running
+    /// on the same datadir.
+    Mmap,
+}
+
+// An open data file
+#[derive(Debug)]
+enum DataFile {
+    File(File),
+    Mmap(Mmap),
+}
+
+impl FreezerTable {
+    /// Opens the index file of the table
+    ///
+    /// The layout of the ancient folder is detected automatically.
+    pub fn open(ancient_folder: &Path, freezer: Freezer) -> Result<Self, FreezerError> {
+        Self::open_with_backend(ancient_folder, freezer, ReadBackend::default())
+    }
+
+    /// Opens the index file of the table with the given read backend
+    pub fn open_with_backend(
+        ancient_folder: &Path,
+        freezer: Freezer,
+        backend: ReadBackend,
+    ) -> Result<Self, FreezerError> {
+        let ancient_folder = freezer.table_folder(ancient_folder);
+        let mut index_file = File::open(ancient_folder.join(freezer.index_filename()))
+            .map_err(FreezerError::OpenFile)?;
+        let index_size = index_file
+            .metadata()
+            .map_err(FreezerError::FileMetadata)?
+            .len();
+        let meta = TableMeta::read(&ancient_folder.join(freezer.meta_filename()))?;
+
+        // The first index entry does not point to an item but stores the number of the first
+        // data file and the number of deleted items
+        let mut first_entry = [0_u8; INDEX_ENTRY_SIZE as usize];
+        index_file
+            .read_exact(&mut first_entry)
+            .map_err(FreezerError::ReadFile)?;
+        let first_entry = IndexEntry::from_bytes(&first_entry)?;
+        let deleted = first_entry.offset as u64;
+        let tail = TableTail {
+            file_number: first_entry.file_number,
+            deleted,
+            hidden: meta.map_or(deleted, |meta| meta.virtual_tail.max(deleted)),
+        };
+
+        // The index contains one more entry than there are items left in the table
+        let items = deleted + (index_size / INDEX_ENTRY_SIZE).saturating_sub(1);
+        info!(
+            "Opened freezer table {} in {} ({}) with blocks {}-{}.",
+            freezer,
+            ancient_folder.display(),
+            meta.map_or(String::from("legacy layout"), |meta| format!(
+                "metadata version {}",
+                meta.version
+            )),
+            tail.hidden,
+            items
+        );
+
+        let index_map = match backend {
+            ReadBackend::File => None,
+            ReadBackend::Mmap => Some(map_file(&index_file)?),
+        };
+
+        Ok(FreezerTable {
+            freezer,
+            ancient_folder,
+            backend,
+            index_file,
+            index_map,
+            meta,
+            tail,
+            items,
+            data_file: None,
+        })
+    }
+
+    /// The freezer variant of this table
+    pub fn freezer(&self) -> Freezer {
+        self.freezer
+    }
+
+    /// The backend which is used to read the files of this table
+    pub fn backend(&self) -> ReadBackend {
+        self.backend
+    }
+
+    /// The folder which contains the files of this table
+    pub fn ancient_folder(&self) -> &Path {
+        &self.ancient_folder
+    }
+
+    /// The metadata of this table, `None` for the legacy layout without metadata files
+    pub fn meta(&self) -> Option<TableMeta> {
+        self.meta
+    }
+
+    /// The tail of this table, i.e. the items which have been pruned
+    pub fn tail(&self) -> TableTail {
+        self.tail
+    }
+
+    /// The number of items in this table, including pruned items
+    ///
+    /// This is the number of the first block which is not yet part of the table.
+    pub fn len(&self) -> u64 {
+        self.items
+    }
+
+    /// Returns true if the table does not contain any items
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    /// Describes the files and the items of this table
+    pub fn info(&self) -> Result<TableInfo, FreezerError> {
+        let index_size = self
+            .index_file
+            .metadata()
+            .map_err(FreezerError::FileMetadata)?
+            .len();
+
+        // Collect all data files of this table which are present in the folder
+        let mut data_files = vec![];
+        let prefix = format!("{}.", self.freezer.table_name());
+        for entry in std::fs::read_dir(&self.ancient_folder).map_err(FreezerError::OpenFile)? {
+            let entry = entry.map_err(FreezerError::OpenFile)?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let file_number = name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.split('.').next())
+                .and_then(|number| number.parse::<u16>().ok());
+            if let Some(file_number) = file_number {
+                if name == self.freezer.data_filename(file_number) {
+                    data_files.push(DataFileInfo {
+                        file_number,
+                        name,
+                        size: entry.metadata().map_err(FreezerError::FileMetadata)?.len(),
+                    });
+                }
+            }
+        }
+        data_files.sort_by_key(|data_file| data_file.file_number);
+
+        // Deleted items are not part of the data files anymore
+        let stored_items = self.items - self.tail.deleted;
+        let data_size = data_files
+            .iter()
+            .map(|data_file| data_file.size)
+            .sum::<u64>();
+        let (first_block, last_block) = if self.items > self.tail.hidden {
+            (Some(self.tail.hidden), Some(self.items - 1))
+        } else {
+            (None, None)
+        };
+
+        Ok(TableInfo {
+            table: self.freezer.to_string(),
+            folder: self.ancient_folder.clone(),
+            meta: self.meta,
+            tail: self.tail,
+            index_size,
+            items: self.items,
+            first_block,
+            last_block,
+            data_files,
+            compressed: self.freezer.is_compressed(),
+            average_item_size: data_size.checked_div(stored_items).unwrap_or(0),
+        })
+    }
+
+    /// Reads the raw bytes of a single item
+    ///
+    /// The returned bytes are already decompressed.
+    pub fn read(&mut self, block_number: u64) -> Result<Vec<u8>, FreezerError> {
+        let freezer = self.freezer;
+        let stored = self.read_stored(block_number)?;
+        Ok(freezer.decompress(&stored)?.into_owned())
+    }
+
+    /// Reads and deserializes a single item
+    pub fn get<T: DeserializeOwned>(&mut self, block_number: u64) -> Result<T, FreezerError> {
+        let freezer = self.freezer;
+        let stored = self.read_stored(block_number)?;
+        freezer.deserialize(&freezer.decompress(&stored)?)
+    }
+
+    // Reads the bytes of a single item as they are stored in the data file
+    //
+    // For the mmap backend the bytes are borrowed from the mapped data file.
+    pub(crate) fn read_stored(&mut self, block_number: u64) -> Result<Cow<'_, [u8]>, FreezerError> {
+        let item = self.read_index(block_number..block_number + 1)?[0];
+        trace!(
+            "Reading block {} from {} at bytes {}-{}",
+            block_number,
+            self.freezer.data_filename(item.file_number),
+            item.start,
+            item.end
+        );
+
+        match self.data_file(item.file_number)? {
+            DataFile::File(data_file) => {
+                let _ = data_file
+                    .seek(SeekFrom::Start(item.start))
+                    .map_err(FreezerError::SeekFile)?;
+                let mut raw = vec![0_u8; item.len() as usize];
+                data_file
+                    .read_exact(&mut raw)
+                    .map_err(FreezerError::ReadFile)?;
+                Ok(Cow::Owned(raw))
+            }
+            DataFile::Mmap(data_map) => Ok(Cow::Borrowed(
+                data_map
+                    .get(item.start as usize..item.end as usize)
+                    .ok_or_else(|| FreezerError::ReadFile(ErrorKind::UnexpectedEof.into()))?,
+            )),
+        }
+    }
+
+    /// Returns an iterator over a range of blocks
+    ///
+    /// The iterator yields the block number together with the deserialized item. It stops after
+    /// the first error.
+    pub fn iter_range<T: DeserializeOwned>(&mut self, range: Range<u64>) -> FreezerIter<'_, T> {
+        FreezerIter {
+            table: self,
+            range,
+            _item: PhantomData,
+        }
+    }
+
+    /// Reads the locations of a range of items from the index file
+    pub fn read_index(&mut self, range: Range<u64>) -> Result<Vec<ItemSpan>, FreezerError> {
+        if range.start >= range.end {
+            return Err(FreezerError::BlockRange);
+        }
+        if range.start < self.tail.hidden {
+            return Err(FreezerError::Pruned(range.start, self.tail.hidden));
+        }
+        if range.end > self.items {
+            return Err(FreezerError::OutOfBounds(range.end - 1, self.items));
+        }
+
+        // Read one more entry than there are items, because the end of an item is stored in the
+        // entry of the next item
+        let position = range.start - self.tail.deleted;
+        let index_start = position * INDEX_ENTRY_SIZE;
+        let index_end = index_start + (range.end - range.start + 1) * INDEX_ENTRY_SIZE;
+        let raw_index = match self.index_map {
+            Some(ref index_map) => Cow::Borrowed(
+                index_map
+                    .get(index_start as usize..index_end as usize)
+                    .ok_or_else(|| FreezerError::ReadFile(ErrorKind::UnexpectedEof.into()))?,
+            ),
+            None => {
+                let mut raw_index = vec![0_u8; (index_end - index_start) as usize];
+                let _ = self
+                    .index_file
+                    .seek(SeekFrom::Start(index_start))
+                    .map_err(FreezerError::SeekFile)?;
+                self.index_file
+                    .read_exact(&mut raw_index)
+                    .map_err(FreezerError::ReadFile)?;
+                Cow::Owned(raw_index)
+            }
+        };
+
+        let mut entries = raw_index
+            .chunks_exact(INDEX_ENTRY_SIZE as usize)
+            .map(IndexEntry::from_bytes)
+            .collect::<Result<Vec<IndexEntry>, FreezerError>>()?;
+
+        // The first entry stores the tail of the table, so the first item always starts at the
+        // beginning of its data file
+        if position == 0 {
+            entries[0] = IndexEntry {
+                file_number: entries[1].file_number,
+                offset: 0,
+            };
+        }
+
+        Ok(entries
+            .windows(2)
+            .map(|entries| ItemSpan::new(entries[0], entries[1]))
+            .collect())
+    }
+
+    // Returns the data file with the given number and keeps it open for subsequent reads
+    fn data_file(&mut self, file_number: u16) -> Result<&mut DataFile, FreezerError> {
+        if !matches!(self.data_file, Some((open_number, _)) if open_number == file_number) {
+            debug!(
+                "Opening data file {}.",
+                self.freezer.data_filename(file_number)
+            );
+            let file = File::open(
+                self.ancient_folder
+                    .join(self.freezer.data_filename(file_number)),
+            )
+            .map_err(FreezerError::OpenFile)?;
+            let data_file = match self.backend {
+                ReadBackend::File => DataFile::File(file),
+                ReadBackend::Mmap => DataFile::Mmap(map_file(&file)?),
+            };
+            self.data_file = Some((file_number, data_file));
+        }
+        Ok(&mut self.data_file.as_mut().expect("Should be impossible.").1)
+    }
+}
+
+// Memory-maps a whole file read-only
+fn map_file(file: &File) -> Result<Mmap, FreezerError> {
+    // Safety: geth only appends to the freezer files while it is running. Mapped files must not
+    // be truncated, see `ReadBackend::Mmap`.
+    unsafe { Mmap::map(file) }.map_err(FreezerError::MapFile)
+}
+
+/// The metadata file of a freezer table, written by newer geth versions
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct TableMeta {
+    pub version: u64,
+    pub virtual_tail: u64,
+}
+
+impl TableMeta {
+    /// Reads the metadata file if it exists
+    pub fn read(meta_filename: &Path) -> Result<Option<TableMeta>, FreezerError> {
+        if !meta_filename.exists() {
+            return Ok(None);
+        }
+        let mut raw = vec![];
+        let _ = File::open(meta_filename)
+            .map_err(FreezerError::OpenFile)?
+            .read_to_end(&mut raw)
+            .map_err(FreezerError::ReadFile)?;
+
+        // Version 2 appends the flush offset of the index file, which we do not need for reading
+        let mut deserializer =
+            RlpDeserializer::new(&raw).map_err(FreezerError::RlpDeserialization)?;
+        let meta =
+            TableMeta::deserialize(&mut deserializer).map_err(FreezerError::RlpDeserialization)?;
+        Ok(Some(meta))
+    }
+}
+
+/// Describes a freezer table, see [`FreezerTable::info`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TableInfo {
+    pub table: String,
+    pub folder: PathBuf,
+    pub meta: Option<TableMeta>,
+    pub tail: TableTail,
+    pub index_size: u64,
+    pub items: u64,
+    pub first_block: Option<u64>,
+    pub last_block: Option<u64>,
+    pub data_files: Vec<DataFileInfo>,
+    pub compressed: bool,
+    pub average_item_size: u64,
+}
+
+impl std::fmt::Display for TableInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// A data file of a freezer table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DataFileInfo {
+    pub file_number: u16,
+    pub name: String,
+    pub size: u64,
+}
+
+/// Describes which items have been removed from the tail of a freezer table
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TableTail {
+    /// The number of the first data file
+    pub file_number: u16,
+    /// The number of items which have been deleted from the data files
+    pub deleted: u64,
+    /// The number of items which cannot be read anymore, i.e. the first available block
+    pub hidden: u64,
+}
+
+/// Iterator over a range of items of a [`FreezerTable`]
+#[derive(Debug)]
+pub struct FreezerIter<'a, T> {
+    table: &'a mut FreezerTable,
+    range: Range<u64>,
+    _item: PhantomData<T>,
+}
+
+impl<'a, T: DeserializeOwned> Iterator for FreezerIter<'a, T> {
+    type Item = Result<(u64, T), FreezerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block_number = self.range.next()?;
+        let item = self.table.get::<T>(block_number);
+        if item.is_err() {
+            self.range = self.range.end..self.range.end;
+        }
+        Some(item.map(|item| (block_number, item)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rlp::to_bytes;
+    use crate::types::{BlockBody, BlockHeader, Receipts, TotalDifficulty};
+    use serde::Serialize;
+
+    #[test]
+    fn test_table_iter_range() {
+        let ancient_folder = PathBuf::from("./fixtures/headers");
+        let mut table = FreezerTable::open(&ancient_folder, Freezer::Headers).unwrap();
+        assert_eq!(table.len(), 100);
+
+        let streamed = table
+            .iter_range::<BlockHeader>(3..10)
+            .map(|item| item.unwrap())
+            .collect::<Vec<(u64, BlockHeader)>>();
+        assert_eq!(
+            streamed.iter().map(|(k, _)| *k).collect::<Vec<u64>>(),
+            (3..10).collect::<Vec<u64>>()
+        );
+        for (block_number, header) in &streamed {
+            assert_eq!(header.number.to_string(), block_number.to_string());
+        }
+
+        // The streamed items must match the output of the in-memory export
+        let index = Freezer::Headers.init(&ancient_folder, 3, 10).unwrap();
+        let data = Freezer::Headers
+            .load_data(&ancient_folder, &index.items)
+            .unwrap();
+        let exported = Freezer::Headers
+            .export_json::<BlockHeader>(&index.items, &data)
+            .unwrap();
+        let streamed_json = streamed
+            .iter()
+            .map(|(_, header)| header.to_string() + ",\n")
+            .collect::<String>();
+        assert_eq!(exported, streamed_json);
+    }
+
+    #[test]
+    fn test_table_rlp_round_trip() {
+        fn assert_round_trip<T: DeserializeOwned + Serialize>(
+            ancient_folder: &str,
+            freezer: Freezer,
+            range: Range<u64>,
+        ) {
+            let mut table = FreezerTable::open(&PathBuf::from(ancient_folder), freezer).unwrap();
+            for block_number in range {
+                let item: T = table.get(block_number).unwrap();
+                assert_eq!(
+                    to_bytes(&item).unwrap(),
+                    table.read(block_number).unwrap(),
+                    "{} {}",
+                    freezer,
+                    block_number
+                );
+            }
+        }
+
+        assert_round_trip::<BlockHeader>("./fixtures/headers", Freezer::Headers, 0..99);
+        assert_round_trip::<BlockBody>("./fixtures/bodies", Freezer::Bodies, 46140..46200);
+        assert_round_trip::<TotalDifficulty>("./fixtures/full", Freezer::Difficulty, 0..3);
+        assert_round_trip::<Receipts>("./fixtures/full", Freezer::Receipts, 0..3);
+    }
+
+    #[test]
+    fn test_table_mmap_backend() {
+        for ancient_folder in [
+            "./fixtures/headers",
+            "./fixtures/split",
+            "./fixtures/pruned",
+        ] {
+            let ancient_folder = PathBuf::from(ancient_folder);
+            let mut file_table = FreezerTable::open(&ancient_folder, Freezer::Headers).unwrap();
+            let mut mmap_table = FreezerTable::open_with_backend(
+                &ancient_folder,
+                Freezer::Headers,
+                ReadBackend::Mmap,
+            )
+            .unwrap();
+            assert_eq!(mmap_table.backend(), ReadBackend::Mmap);
+            assert_eq!(file_table.len(), mmap_table.len());
+
+            let first_block = file_table.tail().hidden;
+            for block_number in first_block..file_table.len().min(99) {
+                assert_eq!(
+                    file_table.read(block_number).unwrap(),
+                    mmap_table.read(block_number).unwrap()
+                );
+            }
+            assert!(matches!(
+                mmap_table.read(mmap_table.len()),
+                Err(FreezerError::OutOfBounds(_, _))
+            ));
+        }
+
+        // Item 99 of the headers fixture is truncated
+        let mut mmap_table = FreezerTable::open_with_backend(
+            &PathBuf::from("./fixtures/headers"),
+            Freezer::Headers,
+            ReadBackend::Mmap,
+        )
+        .unwrap();
+        assert!(matches!(
+            mmap_table.read(99),
+            Err(FreezerError::ReadFile(_))
+        ));
+    }
+
+    #[test]
+    fn test_table_iter_across_data_files() {
+        let mut split_table =
+            FreezerTable::open(&PathBuf::from("./fixtures/split"), Freezer::Headers).unwrap();
+        let mut single_table =
+            FreezerTable::open(&PathBuf::from("./fixtures/headers"), Freezer::Headers).unwrap();
+        assert_eq!(split_table.len(), 21);
+
+        let split = split_table
+            .iter_range::<BlockHeader>(0..21)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let single = single_table
+            .iter_range::<BlockHeader>(0..21)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(split, single);
+    }
+
+    #[test]
+    fn test_table_pruned_tail() {
+        // The pruned fixture uses the layout of newer geth versions. Blocks 0-4 have been deleted
+        // and blocks 5-7 are hidden by the metadata file. The remaining blocks up to block 20
+        // are stored in headers.0001.cdat and headers.0002.cdat.
+        let mut pruned_table =
+            FreezerTable::open(&PathBuf::from("./fixtures/pruned"), Freezer::Headers).unwrap();
+        let mut single_table =
+            FreezerTable::open(&PathBuf::from("./fixtures/headers"), Freezer::Headers).unwrap();
+
+        assert_eq!(
+            pruned_table.ancient_folder(),
+            Path::new("./fixtures/pruned/chain")
+        );
+        assert_eq!(
+            pruned_table.meta(),
+            Some(TableMeta {
+                version: 2,
+                virtual_tail: 8
+            })
+        );
+        assert_eq!(
+            pruned_table.tail(),
+            TableTail {
+                file_number: 1,
+                deleted: 5,
+                hidden: 8
+            }
+        );
+        assert_eq!(pruned_table.len(), 21);
+        assert!(matches!(
+            pruned_table.get::<BlockHeader>(7),
+            Err(FreezerError::Pruned(7, 8))
+        ));
+
+        let pruned = pruned_table
+            .iter_range::<BlockHeader>(8..21)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let single = single_table
+            .iter_range::<BlockHeader>(8..21)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(pruned, single);
+
+        // The batch export resolves the chain folder and the tail in the same way
+        let index = Freezer::Headers
+            .init(&PathBuf::from("./fixtures/pruned"), 8, 21)
+            .unwrap();
+        assert_eq!(
+            index.ancient_folder,
+            PathBuf::from("./fixtures/pruned/chain")
+        );
+        assert_eq!(index.files().count(), 2);
+    }
+
+    #[test]
+    fn test_table_info() {
+        let table =
+            FreezerTable::open(&PathBuf::from("./fixtures/pruned"), Freezer::Headers).unwrap();
+        let info = table.info().unwrap();
+
+        assert_eq!(info.table, "headers");
+        assert_eq!(info.index_size, 102);
+        assert_eq!(info.items, 21);
+        assert_eq!(info.first_block, Some(8));
+        assert_eq!(info.last_block, Some(20));
+        assert_eq!(
+            info.data_files,
+            vec![
+                DataFileInfo {
+                    file_number: 1,
+                    name: String::from("headers.0001.cdat"),
+                    size: 2349
+                },
+                DataFileInfo {
+                    file_number: 2,
+                    name: String::from("headers.0002.cdat"),
+                    size: 2351
+                }
+            ]
+        );
+        assert!(info.compressed);
+        assert_eq!(info.average_item_size, 4700 / 16);
+    }
+
+    #[test]
+    fn test_table_out_of_bounds() {
+        let ancient_folder = PathBuf::from("./fixtures/headers");
+        let mut table = FreezerTable::open(&ancient_folder, Freezer::Headers).unwrap();
+
+        assert!(table.get::<BlockHeader>(98).is_ok());
+        let mut iter = table.iter_range::<BlockHeader>(100..102);
+        assert!(matches!(
+            iter.next(),
+            Some(Err(FreezerError::OutOfBounds(100, 100)))
+        ));
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::crypto::keccak256;
+use crate::rlp::{encode_bytes, encode_list, to_bytes};
+use crate::types::ByteArray;
+
+/// Computes the root hash of a Merkle Patricia Trie
+///
+/// The keys have to be unique.
+pub fn trie_root<K, V>(items: impl IntoIterator<Item = (K, V)>) -> ByteArray<32>
+where
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let mut items = items
+        .into_iter()
+        .map(|(key, value)| (nibbles(key.as_ref()), value))
+        .collect::<Vec<_>>();
+    items.sort_by(|(a, _), (b, _)| a.cmp(b));
+    keccak256(&encode_node(&items, 0))
+}
+
+/// Computes the root hash of a trie whose keys are the RLP encoded indices of the values
+///
+/// This is how the transactions, receipts and withdrawals of a block are committed to.
+pub fn ordered_trie_root<V: AsRef<[u8]>>(values: impl IntoIterator<Item = V>) -> ByteArray<32> {
+    trie_root(values.into_iter().enumerate().map(|(index, value)| {
+        (
+            to_bytes(&(index as u64)).expect("Should be impossible."),
+            value,
+        )
+    }))
+}
+
+// Encodes the node of the sorted items, whose keys are equal for the first `depth` nibbles
+fn encode_node<V: AsRef<[u8]>>(items: &[(Vec<u8>, V)], depth: usize) -> Vec<u8> {
+    let (first_key, last_key) = match items {
+        [] => return encode_bytes(&[]),
+        [(key, value)] => {
+            return encode_list(&[
+                encode_bytes(&hex_prefix(&key[depth..], true)),
+                encode_bytes(value.as_ref()),
+            ])
+        }
+        [(first_key, _), .., (last_key, _)] => (first_key, last_key),
+    };
+
+    // Since the keys are sorted, the first and the last key share the shortest prefix
+    let shared = first_key[depth..]
+        .iter()
+        .zip(&last_key[depth..])
+        .take_while(|(a, b)| a == b)
+        .count();
+    if shared > 0 {
+        return encode_list(&[
+            encode_bytes(&hex_prefix(&first_key[depth..depth + shared], false)),
+            reference(encode_node(items, depth + shared)),
+        ]);
+    }
+
+    // A key which ends here is stored in the branch itself, all others go to one of 16 children
+    let (value, mut rest): (&[u8], _) = match items {
+        [(key, value), rest @ ..] if key.len() == depth => (value.as_ref(), rest),
+        _ => (&[], items),
+    };
+    let mut branch = Vec::with_capacity(17);
+    for nibble in 0..16 {
+        let len = rest
+            .iter()
+            .take_while(|(key, _)| key[depth] == nibble)
+            .count();
+        let (children, remaining) = rest.split_at(len);
+        branch.push(match children {
+            [] => encode_bytes(&[]),
+            children => reference(encode_node(children, depth + 1)),
+        });
+        rest = remaining;
+    }
+    branch.push(encode_bytes(value));
+    encode_list(&branch)
+}
+
+// Nodes shorter than a hash are embedded into their parent
+fn reference(node: Vec<u8>) -> Vec<u8> {
+    if node.len() < 32 {
+        return node;
+    }
+    encode_bytes(&keccak256(&node).0)
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+// Packs nibbles into bytes with a flag for leaves and odd lengths in the first nibble
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let (first, rest) = if nibbles.len() % 2 == 1 {
+        ((flag + 1) << 4 | nibbles[0], &nibbles[1..])
+    } else {
+        (flag << 4, nibbles)
+    };
+    let mut bytes = vec![first];
+    bytes.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trie_root() {
+        // These roots are taken from the trie tests of the Ethereum test suite
+        let empty: [(&[u8], &[u8]); 0] = [];
+        assert_eq!(
+            trie_root(empty).to_string(),
+            "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+        );
+        assert_eq!(
+            trie_root([("A", "a".repeat(50))]).to_string(),
+            "0xd23786fb4a010da3ce639d66d5e904a11dbc02746d1ce25029e53290cabf28ab"
+        );
+        assert_eq!(
+            trie_root([
+                ("do", "verb"),
+                ("horse", "stallion"),
+                ("doge", "coin"),
+                ("dog", "puppy")
+            ])
+            .to_string(),
+            "0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+        );
+        assert_eq!(
+            trie_root([
+                ("doe", "reindeer"),
+                ("dog", "puppy"),
+                ("dogglesworth", "cat")
+            ])
+            .to_string(),
+            "0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+        );
+    }
+
+    #[test]
+    fn test_hex_prefix() {
+        assert_eq!(hex_prefix(&[1, 2, 3, 4, 5], false), vec![0x11, 0x23, 0x45]);
+        assert_eq!(
+            hex_prefix(&[0, 1, 2, 3, 4, 5], false),
+            vec![0x00, 0x01, 0x23, 0x45]
+        );
+        assert_eq!(
+            hex_prefix(&[0, 15, 1, 12, 11, 8], true),
+            vec![0x20, 0x0f, 0x1c, 0xb8]
+        );
+        assert_eq!(
+            hex_prefix(&[15, 1, 12, 11, 8], true),
+            vec![0x3f, 0x1c, 0xb8]
+        );
+    }
+}
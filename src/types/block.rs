@@ -0,0 +1,95 @@
+use super::body::{uncles_view, Uncle};
+use super::{
+    BlockBody, BlockHash, BlockHeader, NiceBigUint, NiceVec, Receipts, TotalDifficulty,
+    TypedTransaction, Withdrawal,
+};
+use serde::{Serialize, Serializer};
+
+/// A complete Ethereum block
+///
+/// Joins the header, hash, total difficulty, body and receipts of a block, which are stored in
+/// separate freezer tables.
+///
+/// When serialized, the block is annotated with its blob base fee, every transaction with the
+/// fields which depend on the block, like its effective gas price, and every uncle with its hash
+/// and inclusion distance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub hash: BlockHash,
+    pub total_difficulty: TotalDifficulty,
+    pub header: BlockHeader,
+    pub body: BlockBody,
+    pub receipts: Receipts,
+}
+
+impl Serialize for Block {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let base_fee = self.header.base_fee_per_gas.as_ref();
+        let blob_base_fee = self.header.blob_base_fee();
+        BlockView {
+            hash: &self.hash,
+            total_difficulty: &self.total_difficulty,
+            header: &self.header,
+            transactions: self
+                .body
+                .transactions
+                .0
+                .iter()
+                .map(|transaction| BlockTransaction {
+                    transaction,
+                    effective_gas_price: transaction.effective_gas_price(base_fee),
+                    blob_fee: match (transaction, &blob_base_fee) {
+                        (TypedTransaction::Blob(blob), Some(blob_base_fee)) => {
+                            Some(blob.blob_fee(blob_base_fee))
+                        }
+                        _ => None,
+                    },
+                })
+                .collect(),
+            uncles: uncles_view(&self.header.number, &self.body.uncles),
+            withdrawals: self.body.withdrawals.as_ref(),
+            receipts: &self.receipts,
+            blob_base_fee,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Serialize)]
+struct BlockView<'a> {
+    hash: &'a BlockHash,
+    total_difficulty: &'a TotalDifficulty,
+    #[serde(flatten)]
+    header: &'a BlockHeader,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blob_base_fee: Option<NiceBigUint>,
+    transactions: Vec<BlockTransaction<'a>>,
+    uncles: Vec<Uncle<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    withdrawals: Option<&'a NiceVec<Withdrawal>>,
+    receipts: &'a Receipts,
+}
+
+// A transaction together with the fields which depend on its block
+#[derive(Serialize)]
+struct BlockTransaction<'a> {
+    #[serde(flatten)]
+    transaction: &'a TypedTransaction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    effective_gas_price: Option<NiceBigUint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blob_fee: Option<NiceBigUint>,
+}
+
+impl std::fmt::Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
@@ -0,0 +1,151 @@
+use super::ByteArray;
+use crate::crypto::keccak256;
+use serde::{Deserialize, Serialize};
+
+/// The 2048-bit bloom filter over the addresses and topics of logs
+///
+/// The header stores the bloom of all logs of the block. A bloom filter can have false
+/// positives, but if it does not contain a value, no log of the block contains it.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Bloom(pub ByteArray<256>);
+
+impl Bloom {
+    /// Adds the address and all topics of a log
+    pub fn accrue_log<'a>(
+        &mut self,
+        address: &ByteArray<20>,
+        topics: impl IntoIterator<Item = &'a ByteArray<32>>,
+    ) {
+        self.accrue(&address.0);
+        for topic in topics {
+            self.accrue(&topic.0);
+        }
+    }
+
+    /// Adds the bits of another bloom
+    pub fn accrue_bloom(&mut self, other: &Bloom) {
+        for (byte, other) in self.0 .0.iter_mut().zip(other.0 .0.iter()) {
+            *byte |= other;
+        }
+    }
+
+    /// Returns false if no log which was added had this address
+    pub fn contains_address(&self, address: &ByteArray<20>) -> bool {
+        self.contains(&address.0)
+    }
+
+    /// Returns false if no log which was added had this topic
+    pub fn contains_topic(&self, topic: &ByteArray<32>) -> bool {
+        self.contains(&topic.0)
+    }
+
+    fn accrue(&mut self, value: &[u8]) {
+        for (byte, mask) in Self::bits(value) {
+            self.0 .0[byte] |= mask;
+        }
+    }
+
+    fn contains(&self, value: &[u8]) -> bool {
+        Self::bits(value).all(|(byte, mask)| self.0 .0[byte] & mask != 0)
+    }
+
+    // Every value sets three of the 2048 bits, chosen by its hash
+    fn bits(value: &[u8]) -> impl Iterator<Item = (usize, u8)> {
+        let hash = keccak256(value);
+        (0..3).map(move |i| {
+            let bit = u16::from_be_bytes([hash.0[2 * i], hash.0[2 * i + 1]]) & 2047;
+            (255 - (bit / 8) as usize, 1 << (bit % 8))
+        })
+    }
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom(ByteArray::<256>([0; 256]))
+    }
+}
+
+impl std::fmt::Display for Bloom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Selects logs by their address and topics
+///
+/// A log matches if it has the address, if one is given, and all of the topics.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    pub address: Option<ByteArray<20>>,
+    pub topics: Vec<ByteArray<32>>,
+}
+
+impl LogFilter {
+    /// Returns false if a block with this bloom cannot contain a matching log
+    pub fn may_match(&self, bloom: &Bloom) -> bool {
+        self.address
+            .as_ref()
+            .is_none_or(|address| bloom.contains_address(address))
+            && self.topics.iter().all(|topic| bloom.contains_topic(topic))
+    }
+
+    /// Checks if a single log matches
+    pub fn matches<'a, I>(&self, address: &ByteArray<20>, topics: I) -> bool
+    where
+        I: IntoIterator<Item = &'a ByteArray<32>>,
+        I::IntoIter: Clone,
+    {
+        let topics = topics.into_iter();
+        self.address.as_ref().is_none_or(|filter| filter == address)
+            && self
+                .topics
+                .iter()
+                .all(|topic| topics.clone().any(|log_topic| log_topic == topic))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom() {
+        let address = ByteArray::<20>([0x11; 20]);
+        let topic = ByteArray::<32>([0x22; 32]);
+        let mut bloom = Bloom::default();
+        assert!(!bloom.contains_address(&address));
+        bloom.accrue_log(&address, std::slice::from_ref(&topic));
+        assert!(bloom.contains_address(&address));
+        assert!(bloom.contains_topic(&topic));
+        assert!(!bloom.contains_topic(&ByteArray::<32>([0x33; 32])));
+        assert_eq!(
+            bloom.0 .0.iter().map(|byte| byte.count_ones()).sum::<u32>(),
+            6
+        );
+
+        let mut other = Bloom::default();
+        other.accrue_log(&ByteArray::<20>([0x44; 20]), &[]);
+        bloom.accrue_bloom(&other);
+        assert!(bloom.contains_address(&ByteArray::<20>([0x44; 20])));
+        assert!(bloom.contains_address(&address));
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let address = ByteArray::<20>([0x11; 20]);
+        let topics = [ByteArray::<32>([0x22; 32]), ByteArray::<32>([0x33; 32])];
+        let mut bloom = Bloom::default();
+        bloom.accrue_log(&address, &topics);
+
+        let filter = LogFilter {
+            address: Some(address.clone()),
+            topics: vec![topics[1].clone()],
+        };
+        assert!(filter.may_match(&bloom));
+        assert!(filter.matches(&address, &topics));
+        assert!(!filter.matches(&address, &topics[..1]));
+        assert!(!filter.may_match(&Bloom::default()));
+        assert!(LogFilter::default().may_match(&Bloom::default()));
+    }
+}
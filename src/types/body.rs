@@ -1,13 +1,48 @@
-use super::{BlockHeader, ByteArray, ByteVec, NiceBigUint, NiceVec};
+use super::{BlockHeader, ByteArray, ByteVec, NiceBigUint, NiceVec, TypedTransaction, Withdrawal};
+use crate::crypto::{keccak256, recover_address};
+use crate::rlp::to_bytes;
+use crate::trie::ordered_trie_root;
+use num_bigint::BigUint;
 use serde::de::Deserializer;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use std::convert::TryFrom;
 
 /// The body of an Ethereum block
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct BlockBody {
-    pub transactions: NiceVec<Transaction>,
+    pub transactions: NiceVec<TypedTransaction>,
     pub uncles: NiceVec<BlockHeader>,
+    /// Added by EIP-4895 in the Shanghai hard fork
+    #[serde(
+        default,
+        deserialize_with = "crate::types::deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub withdrawals: Option<NiceVec<Withdrawal>>,
+}
+
+impl BlockBody {
+    /// The root of the transactions trie, which is stored in the header
+    pub fn transactions_root(&self) -> ByteArray<32> {
+        ordered_trie_root(self.transactions.0.iter().map(TypedTransaction::encode))
+    }
+
+    /// The hash of the RLP list of uncle headers, which is stored in the header as `sha3_uncles`
+    pub fn uncles_hash(&self) -> ByteArray<32> {
+        keccak256(&to_bytes(&self.uncles).expect("Should be impossible."))
+    }
+
+    /// The root of the withdrawals trie, which is stored in the header since Shanghai
+    pub fn withdrawals_root(&self) -> Option<ByteArray<32>> {
+        self.withdrawals.as_ref().map(|withdrawals| {
+            ordered_trie_root(
+                withdrawals
+                    .0
+                    .iter()
+                    .map(|withdrawal| to_bytes(withdrawal).expect("Should be impossible.")),
+            )
+        })
+    }
 }
 
 impl std::fmt::Display for BlockBody {
@@ -20,7 +55,79 @@ impl std::fmt::Display for BlockBody {
     }
 }
 
-/// The transaction object
+/// A block body together with the number of its block
+///
+/// When serialized, every uncle is annotated with its hash and its inclusion distance, which is
+/// the difference between the number of the block and the number of the uncle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberedBody {
+    pub block_number: u64,
+    pub body: BlockBody,
+}
+
+impl Serialize for NumberedBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let block_number = NiceBigUint(BigUint::from(self.block_number));
+        BodyView {
+            transactions: &self.body.transactions,
+            uncles: uncles_view(&block_number, &self.body.uncles),
+            withdrawals: self.body.withdrawals.as_ref(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl std::fmt::Display for NumberedBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct BodyView<'a> {
+    transactions: &'a NiceVec<TypedTransaction>,
+    uncles: Vec<Uncle<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    withdrawals: Option<&'a NiceVec<Withdrawal>>,
+}
+
+// An uncle header together with the fields which depend on the block including it
+#[derive(Serialize)]
+pub(super) struct Uncle<'a> {
+    hash: ByteArray<32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inclusion_distance: Option<NiceBigUint>,
+    #[serde(flatten)]
+    header: &'a BlockHeader,
+}
+
+/// Annotates the uncles of a block with their hashes and inclusion distances
+///
+/// The distance is left out if an uncle is not older than the block, which is invalid.
+pub(super) fn uncles_view<'a>(
+    block_number: &NiceBigUint,
+    uncles: &'a NiceVec<BlockHeader>,
+) -> Vec<Uncle<'a>> {
+    uncles
+        .0
+        .iter()
+        .map(|header| Uncle {
+            hash: header.hash(),
+            inclusion_distance: (block_number.0 > header.number.0)
+                .then(|| NiceBigUint(&block_number.0 - &header.number.0)),
+            header,
+        })
+        .collect()
+}
+
+/// A legacy transaction, which is an RLP list in the block body
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Transaction {
     #[serde(serialize_with = "crate::types::str_serialize")]
@@ -28,18 +135,85 @@ pub struct Transaction {
     pub gas_price: NiceBigUint,
     #[serde(serialize_with = "crate::types::str_serialize")]
     pub gas: u64,
-    #[serde(deserialize_with = "deserialize_transaction")]
+    #[serde(
+        deserialize_with = "deserialize_transaction",
+        serialize_with = "serialize_transaction"
+    )]
     pub to: To,
     pub value: NiceBigUint,
     pub data: ByteVec,
-    #[serde(serialize_with = "crate::types::str_serialize")]
-    v: u8,
-    #[serde(deserialize_with = "deserialize_signature")]
+    v: NiceBigUint,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
     r: ByteArray<32>,
-    #[serde(deserialize_with = "deserialize_signature")]
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
     s: ByteArray<32>,
 }
 
+impl Transaction {
+    /// The hash which the sender signed
+    ///
+    /// Since EIP-155 the chain id is part of the signed payload, which is encoded in `v`.
+    pub fn signing_hash(&self) -> ByteArray<32> {
+        let to = Recipient(&self.to);
+        let payload = match self.chain_id() {
+            Some(chain_id) => to_bytes(&(
+                self.nonce,
+                &self.gas_price,
+                self.gas,
+                to,
+                &self.value,
+                &self.data,
+                &chain_id,
+                0_u8,
+                0_u8,
+            )),
+            None => to_bytes(&(
+                self.nonce,
+                &self.gas_price,
+                self.gas,
+                to,
+                &self.value,
+                &self.data,
+            )),
+        };
+        keccak256(&payload.expect("Should be impossible."))
+    }
+
+    /// The address which sent the transaction, recovered from the signature
+    pub fn sender(&self) -> Option<ByteArray<20>> {
+        recover_address(&self.signing_hash(), self.y_parity()?, &self.r, &self.s)
+    }
+
+    /// The keccak-256 hash of the RLP encoded transaction
+    pub fn hash(&self) -> ByteArray<32> {
+        keccak256(&to_bytes(self).expect("Should be impossible."))
+    }
+
+    /// The chain id, which is encoded in `v` as `chain_id * 2 + 35 + y_parity` since EIP-155
+    pub fn chain_id(&self) -> Option<NiceBigUint> {
+        (self.v.0 >= BigUint::from(35_u32)).then(|| NiceBigUint((&self.v.0 - 35_u32) / 2_u32))
+    }
+
+    /// The parity of the y coordinate of the signature, which is encoded in `v`
+    ///
+    /// Returns `None` if `v` is neither 27 or 28 nor a valid EIP-155 value.
+    pub fn y_parity(&self) -> Option<u8> {
+        let v = &self.v.0;
+        if *v != BigUint::from(27_u32) && *v != BigUint::from(28_u32) && *v < BigUint::from(35_u32)
+        {
+            return None;
+        }
+        // Both offsets are odd, so an odd `v` means an even y coordinate
+        Some(u8::from(!v.bit(0)))
+    }
+}
+
 impl std::fmt::Display for Transaction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -50,9 +224,11 @@ impl std::fmt::Display for Transaction {
     }
 }
 
-fn deserialize_transaction<'de, D: Deserializer<'de>>(deserializer: D) -> Result<To, D::Error> {
+pub(super) fn deserialize_transaction<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<To, D::Error> {
     let buf = Vec::<u8>::deserialize(deserializer)?;
-    if buf.len() == 1 {
+    if buf.is_empty() {
         return Ok(To::ContractCreation(ByteArray::<1>([0_u8])));
     }
     let mut out: [u8; 20] = [0; 20];
@@ -60,7 +236,32 @@ fn deserialize_transaction<'de, D: Deserializer<'de>>(deserializer: D) -> Result
     Ok(To::Address(ByteArray::<20>(out)))
 }
 
-fn deserialize_signature<'de, D: Deserializer<'de>>(
+// Contract creations have an empty recipient in RLP
+pub(super) fn serialize_transaction<S: Serializer>(
+    to: &To,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match to {
+        To::ContractCreation(_) if !serializer.is_human_readable() => {
+            serializer.serialize_bytes(&[])
+        }
+        to => to.serialize(serializer),
+    }
+}
+
+/// A recipient which serializes like the `to` field of a transaction
+pub(super) struct Recipient<'a>(pub(super) &'a To);
+
+impl Serialize for Recipient<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_transaction(self.0, serializer)
+    }
+}
+
+pub(super) fn deserialize_signature<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<ByteArray<32>, D::Error> {
     let buf = Vec::<u8>::deserialize(deserializer)?;
@@ -76,6 +277,18 @@ fn deserialize_signature<'de, D: Deserializer<'de>>(
     ))
 }
 
+// Signature values are integers in RLP, so the padding is removed again
+pub(super) fn serialize_signature<S: Serializer>(
+    signature: &ByteArray<32>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        return signature.serialize(serializer);
+    }
+    let leading_zeros = signature.0.iter().take_while(|&&byte| byte == 0).count();
+    serializer.serialize_bytes(&signature.0[leading_zeros..])
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum To {
@@ -95,8 +308,10 @@ impl std::fmt::Display for To {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rlp::RlpDeserializer;
+    use crate::rlp::{to_bytes, RlpDeserializer};
+    use crate::{Freezer, FreezerTable};
     use num_bigint::BigUint;
+    use std::path::PathBuf;
 
     #[test]
     fn test_body_deserialize() {
@@ -115,7 +330,7 @@ mod tests {
         let body = BlockBody::deserialize(&mut body_deserializer).unwrap();
 
         let body_expected = BlockBody {
-            transactions: NiceVec(vec![Transaction {
+            transactions: NiceVec(vec![TypedTransaction::Legacy(Transaction {
                 nonce: 0,
                 gas_price: NiceBigUint(BigUint::from(50000000000000_u64)),
                 gas: 21000,
@@ -124,8 +339,8 @@ mod tests {
                     0xcd, 0xe1, 0xc0, 0xfc, 0x1d, 0xe7, 0x34,
                 ])),
                 value: NiceBigUint(BigUint::from(31337_u32)),
-                data: ByteVec(vec![0_u8]),
-                v: 28_u8,
+                data: ByteVec(vec![]),
+                v: NiceBigUint(BigUint::from(28_u32)),
                 r: ByteArray::<32>([
                     0x88, 0xff, 0x6c, 0xf0, 0xfe, 0xfd, 0x94, 0xdb, 0x46, 0x11, 0x11, 0x49, 0xae,
                     0x4b, 0xfc, 0x17, 0x9e, 0x9b, 0x94, 0x72, 0x1f, 0xff, 0xd8, 0x21, 0xd3, 0x8d,
@@ -136,9 +351,146 @@ mod tests {
                     0x16, 0xb9, 0xb6, 0x75, 0xc1, 0x37, 0xa6, 0xa4, 0x1a, 0x54, 0x8f, 0x7b, 0x60,
                     0xa3, 0x48, 0x4c, 0x06, 0xa3, 0x3a,
                 ]),
-            }]),
+            })]),
             uncles: NiceVec(vec![]),
+            withdrawals: None,
         };
         assert_eq!(body, body_expected);
+        assert_eq!(
+            body.transactions.0[0].sender().unwrap().to_string(),
+            "0xa1e4380a3b1f749673e270229993ee55f35663b4"
+        );
+        assert_eq!(
+            body.transactions.0[0].hash().to_string(),
+            "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060"
+        );
+        // The roots are the ones stored in the header of block 46147
+        assert_eq!(
+            body.transactions_root().to_string(),
+            "0x4513310fcb9f6f616972a3b948dc5d547f280849a87ebb5af0191f98b87be598"
+        );
+        assert_eq!(body.withdrawals_root(), None);
+        assert_eq!(
+            body.uncles_hash().to_string(),
+            "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+        );
+        let TypedTransaction::Legacy(ref transaction) = body.transactions.0[0] else {
+            panic!("Expected a legacy transaction");
+        };
+        assert_eq!(transaction.chain_id(), None);
+        assert_eq!(transaction.y_parity(), Some(1));
+        assert_eq!(to_bytes(&body).unwrap(), body_input);
+    }
+
+    #[test]
+    fn test_body_uncles() {
+        // Block 3 includes an uncle of block 1, the hash is checked against the header of block 3
+        let body: BlockBody =
+            FreezerTable::open(&PathBuf::from("./fixtures/bodies"), Freezer::Bodies)
+                .unwrap()
+                .get(3)
+                .unwrap();
+        let header: BlockHeader =
+            FreezerTable::open(&PathBuf::from("./fixtures/headers"), Freezer::Headers)
+                .unwrap()
+                .get(3)
+                .unwrap();
+        assert_eq!(body.uncles_hash(), header.sha3_uncles);
+
+        let numbered = serde_json::to_value(NumberedBody {
+            block_number: 3,
+            body,
+        })
+        .unwrap();
+        let uncle = &numbered["uncles"][0];
+        assert_eq!(
+            uncle["hash"],
+            "0x5cd50096dbb856a6d1befa6de8f9c20decb299f375154427d90761dc0b101109"
+        );
+        assert_eq!(uncle["number"], "1");
+        assert_eq!(uncle["inclusion_distance"], "2");
+        assert!(numbered.get("withdrawals").is_none());
+    }
+
+    #[test]
+    fn test_transaction_sender() {
+        // The example transaction of EIP-155, which signs the chain id of mainnet
+        let transaction_input: Vec<u8> = vec![
+            0xf8, 0x6c, 0x09, 0x85, 0x04, 0xa8, 0x17, 0xc8, 0x00, 0x82, 0x52, 0x08, 0x94, 0x35,
+            0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+            0x35, 0x35, 0x35, 0x35, 0x35, 0x88, 0x0d, 0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00,
+            0x80, 0x25, 0xa0, 0x28, 0xef, 0x61, 0x34, 0x0b, 0xd9, 0x39, 0xbc, 0x21, 0x95, 0xfe,
+            0x53, 0x75, 0x67, 0x86, 0x60, 0x03, 0xe1, 0xa1, 0x5d, 0x3c, 0x71, 0xff, 0x63, 0xe1,
+            0x59, 0x06, 0x20, 0xaa, 0x63, 0x62, 0x76, 0xa0, 0x67, 0xcb, 0xe9, 0xd8, 0x99, 0x7f,
+            0x76, 0x1a, 0xec, 0xb7, 0x03, 0x30, 0x4b, 0x38, 0x00, 0xcc, 0xf5, 0x55, 0xc9, 0xf3,
+            0xdc, 0x64, 0x21, 0x4b, 0x29, 0x7f, 0xb1, 0x96, 0x6a, 0x3b, 0x6d, 0x83,
+        ];
+        let mut transaction_deserializer = RlpDeserializer::new(&transaction_input).unwrap();
+        let transaction = Transaction::deserialize(&mut transaction_deserializer).unwrap();
+
+        assert_eq!(
+            transaction.signing_hash().to_string(),
+            "0xdaf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+        assert_eq!(
+            transaction.sender().unwrap().to_string(),
+            "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+        );
+        assert_eq!(
+            transaction.hash().to_string(),
+            "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788"
+        );
+        let json = serde_json::to_value(TypedTransaction::Legacy(transaction)).unwrap();
+        assert_eq!(json["from"], "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+        assert_eq!(
+            json["hash"],
+            "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788"
+        );
+        assert!(json.get("type").is_none());
+    }
+
+    #[test]
+    fn test_transaction_large_chain_id() {
+        // A transaction with the chain id 11155111 of Sepolia, which makes `v` a 4 byte integer.
+        // It is not taken from the chain, but signed by hand with the private key from the
+        // example of EIP-155.
+        let transaction_input: Vec<u8> = vec![
+            0xf8, 0x70, 0x03, 0x85, 0x04, 0xa8, 0x17, 0xc8, 0x00, 0x82, 0x52, 0x08, 0x94, 0x35,
+            0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+            0x35, 0x35, 0x35, 0x35, 0x35, 0x88, 0x01, 0x63, 0x45, 0x78, 0x5d, 0x8a, 0x00, 0x00,
+            0x80, 0x84, 0x01, 0x54, 0x6d, 0x71, 0xa0, 0x79, 0x84, 0xde, 0xd7, 0x8b, 0xd6, 0x98,
+            0x98, 0xf5, 0x0f, 0x29, 0xb7, 0x73, 0xe0, 0x83, 0xdc, 0xd0, 0x55, 0x39, 0x31, 0xea,
+            0xa5, 0xbf, 0x1c, 0xe0, 0x8a, 0x76, 0x36, 0x72, 0xa0, 0x9a, 0x0c, 0xa0, 0x58, 0x71,
+            0xd1, 0xaf, 0xa7, 0x5d, 0x3e, 0x3c, 0xe7, 0x22, 0x35, 0xec, 0x45, 0x59, 0x2f, 0x1f,
+            0x8f, 0x17, 0x4e, 0x4c, 0x98, 0xdd, 0x7e, 0x84, 0xf0, 0xd1, 0x14, 0x6a, 0x83, 0x4d,
+            0xa7, 0xfc,
+        ];
+        let mut transaction_deserializer = RlpDeserializer::new(&transaction_input).unwrap();
+        let transaction = Transaction::deserialize(&mut transaction_deserializer).unwrap();
+
+        assert_eq!(transaction.v, NiceBigUint(BigUint::from(22310257_u32)));
+        assert_eq!(
+            transaction.chain_id(),
+            Some(NiceBigUint(BigUint::from(11155111_u32)))
+        );
+        assert_eq!(transaction.y_parity(), Some(0));
+        assert_eq!(
+            transaction.sender().unwrap().to_string(),
+            "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+        );
+        assert_eq!(to_bytes(&transaction).unwrap(), transaction_input);
+
+        let json = serde_json::to_value(TypedTransaction::Legacy(transaction.clone())).unwrap();
+        assert_eq!(json["v"], "22310257");
+        assert_eq!(json["chain_id"], "11155111");
+        assert_eq!(json["y_parity"], "0");
+
+        // Values of `v` which are not a valid signature have no parity and no sender
+        let invalid = Transaction {
+            v: NiceBigUint(BigUint::from(30_u32)),
+            ..transaction
+        };
+        assert_eq!(invalid.y_parity(), None);
+        assert_eq!(invalid.sender(), None);
     }
 }
@@ -1,6 +1,98 @@
 use super::NiceBigUint;
+use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
+/// The minimum difficulty of a proof-of-work block
+pub const MINIMUM_DIFFICULTY: u64 = 131_072;
+// The difficulty changes by multiples of 1/2048 of the parent difficulty
+const DIFFICULTY_BOUND_DIVISOR: u64 = 2048;
+// The difficulty bomb doubles every 100000 blocks
+const EXP_DIFF_PERIOD: u64 = 100_000;
+
+/// The hard forks which changed the difficulty of proof-of-work blocks
+///
+/// From Byzantium on, the forks only delay the difficulty bomb. Blocks since Paris, the merge,
+/// have no difficulty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DifficultyFork {
+    Frontier,
+    Homestead,
+    Byzantium,
+    Constantinople,
+    MuirGlacier,
+    London,
+    ArrowGlacier,
+    GrayGlacier,
+    Paris,
+}
+
+impl DifficultyFork {
+    /// The fork which is active at a block on mainnet
+    pub fn mainnet(block_number: u64) -> Self {
+        match block_number {
+            0..=1_149_999 => Self::Frontier,
+            1_150_000..=4_369_999 => Self::Homestead,
+            4_370_000..=7_279_999 => Self::Byzantium,
+            7_280_000..=9_199_999 => Self::Constantinople,
+            9_200_000..=12_964_999 => Self::MuirGlacier,
+            12_965_000..=13_772_999 => Self::London,
+            13_773_000..=15_049_999 => Self::ArrowGlacier,
+            15_050_000..=15_537_393 => Self::GrayGlacier,
+            _ => Self::Paris,
+        }
+    }
+
+    /// The number of blocks by which the difficulty bomb is delayed
+    pub fn bomb_delay(&self) -> u64 {
+        match self {
+            Self::Frontier | Self::Homestead | Self::Paris => 0,
+            Self::Byzantium => 3_000_000,
+            Self::Constantinople => 5_000_000,
+            Self::MuirGlacier => 9_000_000,
+            Self::London => 9_700_000,
+            Self::ArrowGlacier => 10_700_000,
+            Self::GrayGlacier => 11_400_000,
+        }
+    }
+}
+
+/// Calculates the difficulty of a block from its parent
+///
+/// The time is the difference between the timestamps of the block and its parent.
+pub(super) fn calculate_difficulty(
+    fork: DifficultyFork,
+    block_number: u64,
+    time: i128,
+    parent_difficulty: &BigUint,
+    parent_has_uncles: bool,
+) -> BigUint {
+    // The adjustment is a multiple of the step, which depends on the time between the blocks
+    let adjustment = match fork {
+        DifficultyFork::Paris => return BigUint::default(),
+        DifficultyFork::Frontier if time < 13 => 1,
+        DifficultyFork::Frontier => -1,
+        DifficultyFork::Homestead => (1 - time.div_euclid(10)).max(-99),
+        _ => (1 + parent_has_uncles as i128 - time.div_euclid(9)).max(-99),
+    };
+    let step = parent_difficulty / DIFFICULTY_BOUND_DIVISOR;
+    let change = step * adjustment.unsigned_abs();
+    let mut difficulty = if adjustment >= 0 {
+        parent_difficulty + change
+    } else if &change < parent_difficulty {
+        parent_difficulty - change
+    } else {
+        BigUint::default()
+    };
+    difficulty = difficulty.max(BigUint::from(MINIMUM_DIFFICULTY));
+
+    // The bomb grows exponentially with the block number, which is reduced by the delay
+    let period = block_number.saturating_sub(fork.bomb_delay()) / EXP_DIFF_PERIOD;
+    if period > 1 {
+        difficulty += BigUint::from(1_u8) << (period - 2);
+    }
+    difficulty
+}
+
 /// The total difficulty of an Ethereum block
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct TotalDifficulty(pub NiceBigUint);
@@ -16,15 +108,18 @@ impl Serialize for TotalDifficulty {
     where
         S: serde::Serializer,
     {
-        serializer.collect_str(self)
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rlp::RlpDeserializer;
-    use num_bigint::BigUint;
+    use crate::rlp::{to_bytes, RlpDeserializer};
 
     #[test]
     fn test_difficulty_deserialize() {
@@ -46,5 +141,79 @@ mod tests {
             diff2,
             TotalDifficulty(NiceBigUint(BigUint::from(22_019_797_038_325_u64)))
         );
+        assert_eq!(to_bytes(&diff1).unwrap(), diff1_input);
+        assert_eq!(to_bytes(&diff2).unwrap(), diff2_input);
+    }
+
+    #[test]
+    fn test_difficulty_fork() {
+        assert_eq!(DifficultyFork::mainnet(0), DifficultyFork::Frontier);
+        assert_eq!(
+            DifficultyFork::mainnet(1_150_000),
+            DifficultyFork::Homestead
+        );
+        assert_eq!(DifficultyFork::mainnet(13_772_999), DifficultyFork::London);
+        assert_eq!(
+            DifficultyFork::mainnet(15_537_393),
+            DifficultyFork::GrayGlacier
+        );
+        assert_eq!(DifficultyFork::mainnet(15_537_394), DifficultyFork::Paris);
+        assert_eq!(DifficultyFork::GrayGlacier.bomb_delay(), 11_400_000);
+    }
+
+    #[test]
+    fn test_calculate_difficulty() {
+        // The step is 1000, the bomb adds 2 ** (1_370_000 / 100_000 - 2) at the Byzantium fork
+        let parent_difficulty = BigUint::from(2_048_000_u64);
+        let byzantium = |time, uncles| {
+            calculate_difficulty(
+                DifficultyFork::Byzantium,
+                4_370_000,
+                time,
+                &parent_difficulty,
+                uncles,
+            )
+        };
+        assert_eq!(byzantium(8, false), BigUint::from(2_049_000_u64 + 2048));
+        assert_eq!(byzantium(9, true), BigUint::from(2_049_000_u64 + 2048));
+        assert_eq!(byzantium(18, false), BigUint::from(2_047_000_u64 + 2048));
+        assert_eq!(
+            byzantium(10_000, false),
+            BigUint::from(1_949_000_u64 + 2048)
+        );
+
+        // Homestead adjusts by 10 second intervals and has no bomb delay
+        assert_eq!(
+            calculate_difficulty(
+                DifficultyFork::Homestead,
+                1_150_000,
+                25,
+                &parent_difficulty,
+                false
+            ),
+            BigUint::from(2_047_000_u64 + 512)
+        );
+
+        // The difficulty never falls below the minimum, and is zero since the merge
+        assert_eq!(
+            calculate_difficulty(
+                DifficultyFork::Frontier,
+                1,
+                20,
+                &BigUint::from(MINIMUM_DIFFICULTY),
+                false
+            ),
+            BigUint::from(MINIMUM_DIFFICULTY)
+        );
+        assert_eq!(
+            calculate_difficulty(
+                DifficultyFork::Paris,
+                15_537_394,
+                12,
+                &parent_difficulty,
+                false
+            ),
+            BigUint::default()
+        );
     }
 }
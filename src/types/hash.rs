@@ -18,7 +18,7 @@ impl std::fmt::Display for BlockHash {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rlp::RlpDeserializer;
+    use crate::rlp::{to_bytes, RlpDeserializer};
 
     #[test]
     fn test_hash_deserialize() {
@@ -57,5 +57,7 @@ mod tests {
                 0x22, 0xf1, 0x0b, 0x4d
             ]))
         );
+        assert_eq!(to_bytes(&hash1).unwrap(), hash1_input);
+        assert_eq!(to_bytes(&hash2).unwrap(), hash2_input);
     }
 }
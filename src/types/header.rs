@@ -1,5 +1,10 @@
-use super::{ByteArray, ByteVec, NiceBigUint};
+use super::difficulty::{calculate_difficulty, DifficultyFork};
+use super::{Bloom, ByteArray, ByteVec, NiceBigUint};
+use crate::crypto::keccak256;
+use crate::rlp::to_bytes;
+use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 
 /// The header of an Ethereum block
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -10,7 +15,7 @@ pub struct BlockHeader {
     pub state_root: ByteArray<32>,
     pub transactions_root: ByteArray<32>,
     pub receipts_root: ByteArray<32>,
-    pub logs_bloom: ByteVec,
+    pub logs_bloom: Bloom,
     pub difficulty: NiceBigUint,
     pub number: NiceBigUint,
     pub gas_limit: NiceBigUint,
@@ -20,6 +25,142 @@ pub struct BlockHeader {
     pub extra_data: ByteVec,
     pub mix_hash: ByteArray<32>,
     pub nonce: ByteArray<8>,
+    /// Added by EIP-1559 in the London hard fork
+    #[serde(
+        default,
+        deserialize_with = "crate::types::deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub base_fee_per_gas: Option<NiceBigUint>,
+    /// Added by EIP-4895 in the Shanghai hard fork
+    #[serde(
+        default,
+        deserialize_with = "crate::types::deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub withdrawals_root: Option<ByteArray<32>>,
+    /// Added by EIP-4844 in the Cancun hard fork
+    #[serde(
+        default,
+        deserialize_with = "crate::types::deserialize_some",
+        serialize_with = "crate::types::option_str_serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub blob_gas_used: Option<u64>,
+    /// Added by EIP-4844 in the Cancun hard fork
+    #[serde(
+        default,
+        deserialize_with = "crate::types::deserialize_some",
+        serialize_with = "crate::types::option_str_serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub excess_blob_gas: Option<u64>,
+    /// Added by EIP-4788 in the Cancun hard fork
+    #[serde(
+        default,
+        deserialize_with = "crate::types::deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub parent_beacon_block_root: Option<ByteArray<32>>,
+    /// Added by EIP-7685 in the Prague hard fork
+    #[serde(
+        default,
+        deserialize_with = "crate::types::deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub requests_hash: Option<ByteArray<32>>,
+}
+
+/// The update fraction of the blob base fee from the Cancun hard fork on
+pub const BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN: u64 = 3_338_477;
+/// The update fraction of the blob base fee from the Prague hard fork on, see EIP-7691
+pub const BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE: u64 = 5_007_716;
+/// The update fraction of the blob base fee from the first blob parameter only fork on
+pub const BLOB_BASE_FEE_UPDATE_FRACTION_BPO1: u64 = 8_346_193;
+/// The update fraction of the blob base fee from the second blob parameter only fork on
+pub const BLOB_BASE_FEE_UPDATE_FRACTION_BPO2: u64 = 11_684_671;
+// The update fractions on mainnet, starting at the timestamps of the forks which changed them
+const BLOB_BASE_FEE_UPDATE_FRACTIONS: [(u64, u64); 4] = [
+    (0, BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN),
+    (1_746_612_311, BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE),
+    (1_765_290_071, BLOB_BASE_FEE_UPDATE_FRACTION_BPO1),
+    (1_767_747_671, BLOB_BASE_FEE_UPDATE_FRACTION_BPO2),
+];
+// The keccak-256 hash of an empty RLP list, which is the uncles hash of blocks without uncles
+const EMPTY_UNCLES_HASH: [u8; 32] = [
+    0x1d, 0xcc, 0x4d, 0xe8, 0xde, 0xc7, 0x5d, 0x7a, 0xab, 0x85, 0xb5, 0x67, 0xb6, 0xcc, 0xd4, 0x1a,
+    0xd3, 0x12, 0x45, 0x1b, 0x94, 0x8a, 0x74, 0x13, 0xf0, 0xa1, 0x42, 0xfd, 0x40, 0xd4, 0x93, 0x47,
+];
+
+impl BlockHeader {
+    /// The keccak-256 hash of the RLP encoded header, which identifies the block
+    pub fn hash(&self) -> ByteArray<32> {
+        keccak256(&to_bytes(self).expect("Should be impossible."))
+    }
+
+    /// The difficulty of this block as calculated from its parent
+    ///
+    /// The difficulty rules are chosen by the fork blocks of mainnet. Use
+    /// [`BlockHeader::expected_difficulty_for_fork`] for other networks.
+    pub fn expected_difficulty(&self, parent: &BlockHeader) -> NiceBigUint {
+        let block_number = u64::try_from(&self.number.0).unwrap_or(u64::MAX);
+        self.expected_difficulty_for_fork(parent, DifficultyFork::mainnet(block_number))
+    }
+
+    /// The difficulty of this block as calculated from its parent with the rules of a fork
+    pub fn expected_difficulty_for_fork(
+        &self,
+        parent: &BlockHeader,
+        fork: DifficultyFork,
+    ) -> NiceBigUint {
+        let block_number = u64::try_from(&self.number.0).unwrap_or(u64::MAX);
+        let time = self.time_stamp as i128 - parent.time_stamp as i128;
+        let parent_has_uncles = parent.sha3_uncles.0 != EMPTY_UNCLES_HASH;
+        NiceBigUint(calculate_difficulty(
+            fork,
+            block_number,
+            time,
+            &parent.difficulty.0,
+            parent_has_uncles,
+        ))
+    }
+
+    /// The price per unit of blob gas in this block
+    ///
+    /// The update fraction is chosen by the fork timestamps of mainnet. Use
+    /// [`BlockHeader::blob_base_fee_with_fraction`] for other networks.
+    pub fn blob_base_fee(&self) -> Option<NiceBigUint> {
+        let (_, update_fraction) = BLOB_BASE_FEE_UPDATE_FRACTIONS
+            .iter()
+            .rev()
+            .find(|(time_stamp, _)| self.time_stamp >= *time_stamp)
+            .expect("Should be impossible.");
+        self.blob_base_fee_with_fraction(*update_fraction)
+    }
+
+    /// The price per unit of blob gas in this block for the given update fraction
+    ///
+    /// Returns `None` for blocks before the Cancun hard fork.
+    pub fn blob_base_fee_with_fraction(&self, update_fraction: u64) -> Option<NiceBigUint> {
+        self.excess_blob_gas.map(|excess_blob_gas| {
+            NiceBigUint(fake_exponential(1, excess_blob_gas, update_fraction))
+        })
+    }
+}
+
+// Approximates factor * e ** (numerator / denominator) with integers as specified in EIP-4844
+fn fake_exponential(factor: u64, numerator: u64, denominator: u64) -> BigUint {
+    let numerator = BigUint::from(numerator);
+    let denominator = BigUint::from(denominator);
+    let mut output = BigUint::default();
+    let mut accumulator = BigUint::from(factor) * &denominator;
+    let mut i = 1_u64;
+    while accumulator > BigUint::default() {
+        output += &accumulator;
+        accumulator = accumulator * &numerator / (&denominator * i);
+        i += 1;
+    }
+    output / denominator
 }
 
 impl std::fmt::Display for BlockHeader {
@@ -35,8 +176,10 @@ impl std::fmt::Display for BlockHeader {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rlp::RlpDeserializer;
+    use crate::rlp::{from_bytes, to_bytes, RlpDeserializer};
+    use crate::{Freezer, FreezerTable};
     use num_bigint::BigUint;
+    use std::path::PathBuf;
 
     #[test]
     fn test_header_deserialize() {
@@ -114,7 +257,7 @@ mod tests {
                 0xf8, 0x6e, 0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5,
                 0xe3, 0x63, 0xb4, 0x21,
             ]),
-            logs_bloom: ByteVec(vec![0; 256]),
+            logs_bloom: Bloom::default(),
             difficulty: NiceBigUint(BigUint::from(17171480576_u64)),
             number: NiceBigUint(BigUint::from(1_u32)),
             gas_limit: NiceBigUint(BigUint::from(5000_u32)),
@@ -130,7 +273,198 @@ mod tests {
                 0xc2, 0xe6, 0x6f, 0x59,
             ]),
             nonce: ByteArray::<8>([0x53, 0x9b, 0xd4, 0x97, 0x9f, 0xef, 0x1e, 0xc4]),
+            base_fee_per_gas: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
         };
+        assert_eq!(header.blob_base_fee(), None);
         assert_eq!(header, header_expected);
+        assert_eq!(
+            header.hash().to_string(),
+            "0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6"
+        );
+        assert_eq!(to_bytes(&header).unwrap(), header_input);
+
+        // London headers have a trailing base fee, even if it is zero
+        for base_fee in [0_u64, 7, 1_000_000_000] {
+            let london_header = BlockHeader {
+                base_fee_per_gas: Some(NiceBigUint(BigUint::from(base_fee))),
+                ..header.clone()
+            };
+            let london_input = to_bytes(&london_header).unwrap();
+            assert_eq!(&london_input[3..header_input.len()], &header_input[3..]);
+            assert_eq!(
+                from_bytes::<BlockHeader>(&london_input).unwrap(),
+                london_header
+            );
+        }
+
+        // Shanghai headers add the withdrawals root
+        let shanghai_header = BlockHeader {
+            base_fee_per_gas: Some(NiceBigUint(BigUint::from(7_u32))),
+            withdrawals_root: Some(ByteArray::<32>([0x56; 32])),
+            ..header.clone()
+        };
+        let shanghai_input = to_bytes(&shanghai_header).unwrap();
+        assert_eq!(
+            &shanghai_input[shanghai_input.len() - 34..][..2],
+            &[0x07, 0xa0]
+        );
+        assert_eq!(
+            from_bytes::<BlockHeader>(&shanghai_input).unwrap(),
+            shanghai_header
+        );
+
+        // Cancun headers add the blob gas fields and the parent beacon block root
+        let cancun_header = BlockHeader {
+            blob_gas_used: Some(393216),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(ByteArray::<32>([0x78; 32])),
+            ..shanghai_header
+        };
+        let cancun_input = to_bytes(&cancun_header).unwrap();
+        assert_eq!(
+            from_bytes::<BlockHeader>(&cancun_input).unwrap(),
+            cancun_header
+        );
+        let json = serde_json::to_value(&cancun_header).unwrap();
+        assert_eq!(json["blob_gas_used"], "393216");
+        assert_eq!(json["excess_blob_gas"], "0");
+
+        // Prague headers add the requests hash
+        let prague_header = BlockHeader {
+            requests_hash: Some(ByteArray::<32>([0x9a; 32])),
+            ..cancun_header
+        };
+        let prague_input = to_bytes(&prague_header).unwrap();
+        assert_eq!(&prague_input[prague_input.len() - 33..][..2], &[0xa0, 0x9a]);
+        assert_eq!(
+            from_bytes::<BlockHeader>(&prague_input).unwrap(),
+            prague_header
+        );
+    }
+
+    #[test]
+    fn test_header_blob_base_fee() {
+        let fee = |amount: u64| Some(NiceBigUint(BigUint::from(amount)));
+        assert_eq!(fake_exponential(1, 0, 3338477), BigUint::from(1_u32));
+        assert_eq!(fake_exponential(1, 3338477, 3338477), BigUint::from(2_u32));
+
+        let header = |excess_blob_gas: u64, time_stamp: u64| BlockHeader {
+            excess_blob_gas: Some(excess_blob_gas),
+            time_stamp,
+            ..empty_header()
+        };
+        // Cancun
+        assert_eq!(header(0, 1_710_338_135).blob_base_fee(), fee(1));
+        assert_eq!(
+            header(33_384_770, 1_710_338_135).blob_base_fee(),
+            fee(22026)
+        );
+        // Prague
+        assert_eq!(header(33_384_770, 1_746_612_311).blob_base_fee(), fee(785));
+        assert_eq!(
+            header(50_077_160, 1_746_612_311).blob_base_fee(),
+            fee(22026)
+        );
+        // The blob parameter only forks after Osaka, one second before and at the fork
+        assert_eq!(
+            header(50_077_160, 1_765_290_070).blob_base_fee(),
+            fee(22026)
+        );
+        assert_eq!(header(50_077_160, 1_765_290_071).blob_base_fee(), fee(403));
+        assert_eq!(
+            header(83_461_930, 1_765_290_071).blob_base_fee(),
+            fee(22026)
+        );
+        assert_eq!(
+            header(83_461_930, 1_767_747_670).blob_base_fee(),
+            fee(22026)
+        );
+        assert_eq!(header(83_461_930, 1_767_747_671).blob_base_fee(), fee(1265));
+        assert_eq!(
+            header(116_846_710, 1_767_747_671).blob_base_fee(),
+            fee(22026)
+        );
+        assert_eq!(
+            header(50_077_160, 0).blob_base_fee_with_fraction(BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN),
+            fee(3_269_022)
+        );
+    }
+
+    #[test]
+    fn test_header_expected_difficulty() {
+        // The first blocks of mainnet follow the Frontier rules
+        let mut headers =
+            FreezerTable::open(&PathBuf::from("./fixtures/headers"), Freezer::Headers).unwrap();
+        let mut parent: BlockHeader = headers.get(0).unwrap();
+        for block_number in 1..99 {
+            let header: BlockHeader = headers.get(block_number).unwrap();
+            assert_eq!(header.expected_difficulty(&parent), header.difficulty);
+            parent = header;
+        }
+
+        // Since Byzantium, uncles of the parent increase the difficulty
+        let big = |value: u64| NiceBigUint(BigUint::from(value));
+        let parent = BlockHeader {
+            sha3_uncles: ByteArray::<32>(EMPTY_UNCLES_HASH),
+            difficulty: big(2_048_000),
+            time_stamp: 1000,
+            ..empty_header()
+        };
+        let header = BlockHeader {
+            number: big(4_370_000),
+            time_stamp: 1009,
+            ..empty_header()
+        };
+        assert_eq!(header.expected_difficulty(&parent), big(2_048_000 + 2048));
+        let parent_with_uncles = BlockHeader {
+            sha3_uncles: ByteArray::<32>([0; 32]),
+            ..parent.clone()
+        };
+        assert_eq!(
+            header.expected_difficulty(&parent_with_uncles),
+            big(2_049_000 + 2048)
+        );
+        assert_eq!(
+            header.expected_difficulty_for_fork(&parent, DifficultyFork::Homestead),
+            big(2_049_000 + 2_u64.pow(41))
+        );
+
+        // The merge ends proof-of-work
+        let merge = BlockHeader {
+            number: big(15_537_394),
+            ..header
+        };
+        assert_eq!(merge.expected_difficulty(&parent), big(0));
+    }
+
+    fn empty_header() -> BlockHeader {
+        BlockHeader {
+            parent_hash: ByteArray::<32>([0; 32]),
+            sha3_uncles: ByteArray::<32>([0; 32]),
+            miner: ByteArray::<20>([0; 20]),
+            state_root: ByteArray::<32>([0; 32]),
+            transactions_root: ByteArray::<32>([0; 32]),
+            receipts_root: ByteArray::<32>([0; 32]),
+            logs_bloom: Bloom::default(),
+            difficulty: NiceBigUint(BigUint::default()),
+            number: NiceBigUint(BigUint::default()),
+            gas_limit: NiceBigUint(BigUint::default()),
+            gas_used: NiceBigUint(BigUint::default()),
+            time_stamp: 0,
+            extra_data: ByteVec(vec![]),
+            mix_hash: ByteArray::<32>([0; 32]),
+            nonce: ByteArray::<8>([0; 8]),
+            base_fee_per_gas: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
+        }
     }
 }
@@ -1,17 +1,47 @@
 use num_bigint::BigUint;
+use serde::de::{Error as _, Unexpected};
 use serde::{Deserialize, Serialize};
 
+mod block;
+mod bloom;
 mod body;
 mod difficulty;
 mod hash;
 mod header;
 mod receipt;
+mod transaction;
+mod withdrawal;
 
-pub use body::BlockBody;
-pub use difficulty::TotalDifficulty;
+pub use block::Block;
+pub use bloom::{Bloom, LogFilter};
+pub use body::{BlockBody, NumberedBody, To, Transaction};
+pub use difficulty::{DifficultyFork, TotalDifficulty, MINIMUM_DIFFICULTY};
 pub use hash::BlockHash;
 pub use header::BlockHeader;
 pub use receipt::Receipts;
+pub use transaction::{
+    AccessListItem, AccessListTransaction, Authorization, BlobTransaction, DynamicFeeTransaction,
+    SetCodeTransaction, TypedTransaction, UnknownTransaction,
+};
+pub use withdrawal::Withdrawal;
+
+/// A block part annotated with the number of the block it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockItem<T> {
+    #[serde(serialize_with = "str_serialize")]
+    pub block_number: u64,
+    pub data: T,
+}
+
+impl<T: Serialize> std::fmt::Display for BlockItem<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
 
 /// A const-sized byte array for types of known byte length
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -33,14 +63,18 @@ impl<const N: usize> Serialize for ByteArray<N> {
     where
         S: serde::Serializer,
     {
-        serializer.collect_str(self)
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
     }
 }
 
 /// A byte vector for types of unknown byte length
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(transparent)]
-pub struct ByteVec(#[serde(serialize_with = "str_serialize")] pub Vec<u8>);
+pub struct ByteVec(pub Vec<u8>);
 
 impl std::fmt::Display for ByteVec {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -57,7 +91,11 @@ impl Serialize for ByteVec {
     where
         S: serde::Serializer,
     {
-        serializer.collect_str(self)
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
     }
 }
 
@@ -82,10 +120,9 @@ impl<T> Default for NiceVec<T> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-#[serde(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// A big uint which can pretty-print for JSON serialization
-pub struct NiceBigUint(#[serde(serialize_with = "str_serialize")] BigUint);
+pub struct NiceBigUint(BigUint);
 
 impl std::fmt::Display for NiceBigUint {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -93,10 +130,87 @@ impl std::fmt::Display for NiceBigUint {
     }
 }
 
+impl NiceBigUint {
+    /// The wrapped big integer
+    pub fn value(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+impl From<BigUint> for NiceBigUint {
+    fn from(value: BigUint) -> Self {
+        NiceBigUint(value)
+    }
+}
+
+impl Serialize for NiceBigUint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else if self.0 == BigUint::default() {
+            serializer.serialize_bytes(&[])
+        } else {
+            serializer.serialize_bytes(&self.0.to_bytes_be())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NiceBigUint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let digits = String::deserialize(deserializer)?;
+            BigUint::parse_bytes(digits.as_bytes(), 10)
+                .map(NiceBigUint)
+                .ok_or_else(|| {
+                    D::Error::invalid_value(Unexpected::Str(&digits), &"a decimal number")
+                })
+        } else {
+            let bytes = Vec::<u8>::deserialize(deserializer)?;
+            Ok(NiceBigUint(BigUint::from_bytes_be(&bytes)))
+        }
+    }
+}
+
+/// Deserializes an optional trailing field which is always `Some` when it is present
+///
+/// Use together with `#[serde(default)]`, so a missing field becomes `None`.
+pub fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Serializes an optional value like [`str_serialize`]
+pub fn option_str_serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: std::fmt::Display + Serialize,
+    S: serde::ser::Serializer,
+{
+    match value {
+        Some(value) => str_serialize(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Serializes a value as a string for human-readable formats like JSON
+///
+/// Other formats serialize the value as it is.
 pub fn str_serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
 where
-    T: std::fmt::Display,
+    T: std::fmt::Display + Serialize,
     S: serde::ser::Serializer,
 {
-    serializer.collect_str(value)
+    if serializer.is_human_readable() {
+        serializer.collect_str(value)
+    } else {
+        value.serialize(serializer)
+    }
 }
@@ -1,11 +1,51 @@
-use super::{ByteArray, ByteVec, NiceBigUint, NiceVec};
+use super::{Bloom, ByteArray, ByteVec, LogFilter, NiceBigUint, NiceVec, TypedTransaction};
+use crate::rlp::to_bytes;
+use crate::trie::ordered_trie_root;
 use serde::de::Deserializer;
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 
 /// The transaction receipt of an Ethereum transaction
+///
+/// The freezer stores receipts in the same format for all transaction types, so the type of a
+/// receipt is only known together with the block body, see [`Receipts::set_transaction_types`].
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Receipts(#[serde(default)] NiceVec<TransactionReceipt>);
 
+impl Receipts {
+    /// Annotates every receipt with the type of the transaction it belongs to
+    pub fn set_transaction_types(&mut self, transactions: &[TypedTransaction]) {
+        for (receipt, transaction) in self.0 .0.iter_mut().zip(transactions) {
+            receipt.transaction_type = Some(transaction.transaction_type());
+        }
+    }
+
+    /// The root of the receipts trie, which is stored in the header
+    ///
+    /// Typed receipts are encoded with their type, so the transaction types need to be set.
+    pub fn root(&self) -> ByteArray<32> {
+        ordered_trie_root(self.0 .0.iter().map(TransactionReceipt::encode))
+    }
+
+    /// The bloom of all logs, which is stored in the header
+    pub fn logs_bloom(&self) -> Bloom {
+        let mut bloom = Bloom::default();
+        for receipt in &self.0 .0 {
+            bloom.accrue_bloom(&receipt.logs_bloom());
+        }
+        bloom
+    }
+
+    /// Checks if any log of the receipts matches the filter
+    pub fn contains_log(&self, filter: &LogFilter) -> bool {
+        self.0
+             .0
+            .iter()
+            .flat_map(|receipt| &receipt.logs.0)
+            .any(|log| filter.matches(&log.address, log.topics()))
+    }
+}
+
 impl std::fmt::Display for Receipts {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -16,8 +56,10 @@ impl std::fmt::Display for Receipts {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct TransactionReceipt {
+    #[serde(skip_deserializing)]
+    transaction_type: Option<u8>,
     #[serde(deserialize_with = "deserialize_post_state")]
     post_state: PostState,
     cum_gas_used: NiceBigUint,
@@ -34,12 +76,60 @@ impl std::fmt::Display for TransactionReceipt {
     }
 }
 
+impl TransactionReceipt {
+    // The consensus encoding adds the bloom filter of the logs, which is not stored in the freezer
+    fn encode(&self) -> Vec<u8> {
+        let payload = to_bytes(&(
+            &self.post_state,
+            &self.cum_gas_used,
+            self.logs_bloom(),
+            &self.logs,
+        ))
+        .expect("Should be impossible.");
+        match self.transaction_type {
+            None | Some(0) => payload,
+            Some(transaction_type) => [vec![transaction_type], payload].concat(),
+        }
+    }
+
+    fn logs_bloom(&self) -> Bloom {
+        let mut bloom = Bloom::default();
+        for log in &self.logs.0 {
+            bloom.accrue_log(&log.address, log.topics());
+        }
+        bloom
+    }
+}
+
+// The transaction type is not part of the stored receipt, so it is only added to JSON
+impl Serialize for TransactionReceipt {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let transaction_type = self
+            .transaction_type
+            .filter(|_| serializer.is_human_readable());
+        let mut state = serializer.serialize_struct(
+            "TransactionReceipt",
+            3 + transaction_type.is_some() as usize,
+        )?;
+        if let Some(transaction_type) = transaction_type {
+            state.serialize_field("type", &transaction_type.to_string())?;
+        }
+        state.serialize_field("post_state", &self.post_state)?;
+        state.serialize_field("cum_gas_used", &self.cum_gas_used)?;
+        state.serialize_field("logs", &self.logs)?;
+        state.end()
+    }
+}
+
 fn deserialize_post_state<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<PostState, D::Error> {
     let buf = Vec::<u8>::deserialize(deserializer)?;
-    if buf.len() == 1 {
-        return Ok(PostState::Success(matches!(buf[0], 0x01)));
+    if buf.len() <= 1 {
+        return Ok(PostState::Success(buf == [0x01]));
     }
     let mut out: [u8; 32] = [0; 32];
     out.copy_from_slice(&buf);
@@ -69,6 +159,12 @@ struct Log {
     data: ByteVec,
 }
 
+impl Log {
+    fn topics(&self) -> impl Iterator<Item = &ByteArray<32>> + Clone {
+        self.topics.0.iter().map(|topic| &topic.0)
+    }
+}
+
 impl std::fmt::Display for Log {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -95,10 +191,10 @@ impl std::fmt::Display for Topics {
 
 #[cfg(test)]
 mod tests {
-    use num_bigint::BigUint;
-
     use super::*;
-    use crate::rlp::RlpDeserializer;
+    use crate::rlp::{from_bytes, to_bytes, RlpDeserializer};
+    use crate::types::UnknownTransaction;
+    use num_bigint::BigUint;
 
     #[test]
     fn test_receipt_deserialize() {
@@ -110,6 +206,7 @@ mod tests {
         ];
 
         let receipt_expected = Receipts(NiceVec(vec![TransactionReceipt {
+            transaction_type: None,
             post_state: PostState::State(ByteArray::<32>([
                 0x96, 0xa8, 0xe0, 0x09, 0xd2, 0xb8, 0x8b, 0x14, 0x83, 0xe6, 0x94, 0x1e, 0x68, 0x12,
                 0xe3, 0x22, 0x63, 0xb0, 0x56, 0x83, 0xfa, 0xc2, 0x02, 0xab, 0xc6, 0x22, 0xa3, 0xe3,
@@ -123,5 +220,94 @@ mod tests {
         let receipt = Receipts::deserialize(&mut receipt_deserializer).unwrap();
 
         assert_eq!(receipt, receipt_expected);
+        assert_eq!(to_bytes(&receipt).unwrap(), receipt_input);
+        assert_eq!(
+            receipt.root().to_string(),
+            "0xfe2bf2a941abf41d72637e5b91750332a30283efd40c424dc522b77e6f0ed8c4"
+        );
+
+        // The transaction type is added to JSON only
+        let mut receipt = receipt;
+        receipt.set_transaction_types(&[TypedTransaction::Unknown(UnknownTransaction {
+            transaction_type: 2,
+            payload: ByteVec(vec![0xc0]),
+        })]);
+        assert_eq!(serde_json::to_value(&receipt).unwrap()[0]["type"], "2");
+        assert_eq!(to_bytes(&receipt).unwrap(), receipt_input);
+    }
+
+    #[test]
+    fn test_receipt_logs_bloom() {
+        let address = ByteArray::<20>([0x11; 20]);
+        let topic = ByteArray::<32>([0x22; 32]);
+        let log = |address: &ByteArray<20>, topics: Vec<ByteArray<32>>| Log {
+            address: address.clone(),
+            topics: NiceVec(topics.into_iter().map(Topics).collect()),
+            data: ByteVec(vec![]),
+        };
+        let receipt = |logs| TransactionReceipt {
+            transaction_type: None,
+            post_state: PostState::Success(true),
+            cum_gas_used: NiceBigUint(BigUint::from(21000_u32)),
+            logs: NiceVec(logs),
+        };
+        let receipts = Receipts(NiceVec(vec![
+            receipt(vec![log(&address, vec![])]),
+            receipt(vec![]),
+            receipt(vec![log(&ByteArray::<20>([0x33; 20]), vec![topic.clone()])]),
+        ]));
+
+        let bloom = receipts.logs_bloom();
+        assert!(bloom.contains_address(&address));
+        assert!(bloom.contains_topic(&topic));
+        assert_eq!(receipts.0 .0[1].logs_bloom(), Bloom::default());
+
+        // The bloom contains both the address and the topic, but no single log has both
+        let filter = LogFilter {
+            address: Some(address),
+            topics: vec![topic],
+        };
+        assert!(filter.may_match(&bloom));
+        assert!(!receipts.contains_log(&filter));
+        let filter = LogFilter {
+            address: None,
+            ..filter
+        };
+        assert!(receipts.contains_log(&filter));
+    }
+
+    #[test]
+    fn test_typed_receipts_root() {
+        // Stored receipts of a legacy, a failed EIP-1559 and an EIP-4844 transaction with a log.
+        // They are encoded by hand, so the root and the bloom are not published anywhere; the
+        // test pins them to catch changes in how the typed receipts are encoded in the trie.
+        let receipts_input: Vec<u8> = vec![
+            0xf8, 0x74, 0xc5, 0x01, 0x82, 0x52, 0x08, 0xc0, 0xc6, 0x80, 0x83, 0x01, 0x21, 0x10,
+            0xc0, 0xf8, 0x65, 0x01, 0x83, 0x01, 0xfb, 0xd0, 0xf8, 0x5e, 0xf8, 0x5c, 0x94, 0x11,
+            0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+            0x11, 0x11, 0x11, 0x11, 0x11, 0xf8, 0x42, 0xa0, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+            0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+            0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0xa0, 0x33,
+            0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33,
+            0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33,
+            0x33, 0x33, 0x33, 0x82, 0xab, 0xcd,
+        ];
+        let mut receipts = from_bytes::<Receipts>(&receipts_input).unwrap();
+        assert_eq!(receipts.0 .0[0].post_state, PostState::Success(true));
+        assert_eq!(receipts.0 .0[1].post_state, PostState::Success(false));
+        assert_eq!(receipts.0 .0[2].logs.0[0].topics.0.len(), 2);
+        assert_eq!(to_bytes(&receipts).unwrap(), receipts_input);
+
+        for (receipt, transaction_type) in receipts.0 .0.iter_mut().zip([0, 2, 3]) {
+            receipt.transaction_type = Some(transaction_type);
+        }
+        assert_eq!(
+            receipts.root().to_string(),
+            "0xc673f3de3c663f5b5db5ef657c766d500cf3c48d722f6bf2afff0ccae13be8cb"
+        );
+        assert_eq!(
+            receipts.logs_bloom().to_string(),
+            "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000002000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000000000000000000100100000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000008000000000000000000000000000000002000000000000000000000000000000000000000000000000"
+        );
     }
 }
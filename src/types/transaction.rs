@@ -0,0 +1,1167 @@
+use super::body::{
+    deserialize_signature, deserialize_transaction, serialize_signature, serialize_transaction,
+    Recipient,
+};
+use super::{ByteArray, ByteVec, NiceBigUint, NiceVec, To, Transaction};
+use crate::crypto::{is_low_s, keccak256, recover_address};
+use crate::rlp::{from_bytes, to_bytes, RlpError};
+use serde::de::{self, SeqAccess, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A transaction of any type
+///
+/// Since EIP-2718 a block body contains legacy transactions as RLP lists and typed transactions
+/// as RLP strings. The string consists of the transaction type followed by the RLP encoded
+/// payload of the transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedTransaction {
+    Legacy(Transaction),
+    AccessList(AccessListTransaction),
+    DynamicFee(DynamicFeeTransaction),
+    Blob(BlobTransaction),
+    SetCode(SetCodeTransaction),
+    Unknown(UnknownTransaction),
+}
+
+impl TypedTransaction {
+    /// The EIP-2718 transaction type, legacy transactions have type 0
+    pub fn transaction_type(&self) -> u8 {
+        match self {
+            TypedTransaction::Legacy(_) => 0,
+            TypedTransaction::AccessList(_) => AccessListTransaction::TYPE,
+            TypedTransaction::DynamicFee(_) => DynamicFeeTransaction::TYPE,
+            TypedTransaction::Blob(_) => BlobTransaction::TYPE,
+            TypedTransaction::SetCode(_) => SetCodeTransaction::TYPE,
+            TypedTransaction::Unknown(transaction) => transaction.transaction_type,
+        }
+    }
+
+    /// The price per gas the sender paid, given the base fee of the block
+    ///
+    /// Returns `None` for dynamic fee transactions without base fee and for unknown types.
+    pub fn effective_gas_price(&self, base_fee: Option<&NiceBigUint>) -> Option<NiceBigUint> {
+        match self {
+            TypedTransaction::Legacy(transaction) => Some(transaction.gas_price.clone()),
+            TypedTransaction::AccessList(transaction) => Some(transaction.gas_price.clone()),
+            TypedTransaction::DynamicFee(transaction) => {
+                base_fee.map(|base_fee| transaction.effective_gas_price(base_fee))
+            }
+            TypedTransaction::Blob(transaction) => {
+                base_fee.map(|base_fee| transaction.effective_gas_price(base_fee))
+            }
+            TypedTransaction::SetCode(transaction) => {
+                base_fee.map(|base_fee| transaction.effective_gas_price(base_fee))
+            }
+            TypedTransaction::Unknown(_) => None,
+        }
+    }
+
+    /// The address which sent the transaction, recovered from the signature
+    ///
+    /// Returns `None` for invalid signatures and for unknown types.
+    pub fn sender(&self) -> Option<ByteArray<20>> {
+        match self {
+            TypedTransaction::Legacy(transaction) => transaction.sender(),
+            TypedTransaction::AccessList(transaction) => transaction.sender(),
+            TypedTransaction::DynamicFee(transaction) => transaction.sender(),
+            TypedTransaction::Blob(transaction) => transaction.sender(),
+            TypedTransaction::SetCode(transaction) => transaction.sender(),
+            TypedTransaction::Unknown(_) => None,
+        }
+    }
+
+    /// The keccak-256 hash of the encoded transaction, which identifies it on the chain
+    pub fn hash(&self) -> ByteArray<32> {
+        keccak256(&self.encode())
+    }
+
+    /// The canonical encoding of the transaction
+    ///
+    /// This is the RLP list for legacy transactions and the type followed by the RLP encoded
+    /// payload for typed transactions.
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            TypedTransaction::Legacy(transaction) => to_bytes(transaction),
+            TypedTransaction::AccessList(transaction) => {
+                encode_envelope(AccessListTransaction::TYPE, transaction)
+            }
+            TypedTransaction::DynamicFee(transaction) => {
+                encode_envelope(DynamicFeeTransaction::TYPE, transaction)
+            }
+            TypedTransaction::Blob(transaction) => {
+                encode_envelope(BlobTransaction::TYPE, transaction)
+            }
+            TypedTransaction::SetCode(transaction) => {
+                encode_envelope(SetCodeTransaction::TYPE, transaction)
+            }
+            TypedTransaction::Unknown(transaction) => {
+                let mut envelope = vec![transaction.transaction_type];
+                envelope.extend_from_slice(&transaction.payload.0);
+                Ok(envelope)
+            }
+        }
+        .expect("Should be impossible.")
+    }
+}
+
+impl std::fmt::Display for TypedTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+impl Serialize for TypedTransaction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            return self.serialize_json(serializer);
+        }
+        // Typed transactions are wrapped in an RLP string
+        match self {
+            TypedTransaction::Legacy(transaction) => transaction.serialize(serializer),
+            transaction => serializer.serialize_bytes(&transaction.encode()),
+        }
+    }
+}
+
+impl TypedTransaction {
+    // Typed transactions are tagged with their type in JSON, and all transactions are annotated
+    // with their hash and sender. Legacy transactions also get the values encoded in `v`.
+    fn serialize_json<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut annotations = Annotations {
+            hash: self.hash(),
+            transaction_type: Some(self.transaction_type()),
+            from: self.sender(),
+            chain_id: None,
+            y_parity: None,
+        };
+        match self {
+            TypedTransaction::Legacy(transaction) => {
+                annotations.transaction_type = None;
+                annotations.chain_id = transaction.chain_id();
+                annotations.y_parity = transaction.y_parity();
+                Tagged {
+                    annotations,
+                    transaction,
+                }
+                .serialize(serializer)
+            }
+            TypedTransaction::AccessList(transaction) => Tagged {
+                annotations,
+                transaction,
+            }
+            .serialize(serializer),
+            TypedTransaction::DynamicFee(transaction) => Tagged {
+                annotations,
+                transaction,
+            }
+            .serialize(serializer),
+            TypedTransaction::Blob(transaction) => Tagged {
+                annotations,
+                transaction,
+            }
+            .serialize(serializer),
+            TypedTransaction::SetCode(transaction) => Tagged {
+                annotations,
+                transaction,
+            }
+            .serialize(serializer),
+            // Unknown transactions carry their own type
+            TypedTransaction::Unknown(transaction) => {
+                annotations.transaction_type = None;
+                Tagged {
+                    annotations,
+                    transaction,
+                }
+                .serialize(serializer)
+            }
+        }
+    }
+}
+
+// Typed transactions are encoded as their type followed by the RLP encoded payload
+fn encode_envelope<T: Serialize>(
+    transaction_type: u8,
+    transaction: &T,
+) -> Result<Vec<u8>, RlpError> {
+    let mut envelope = vec![transaction_type];
+    envelope.extend(to_bytes(transaction)?);
+    Ok(envelope)
+}
+
+// EIP-1559 pays the base fee plus the priority fee, capped by the maximum fee
+fn effective_gas_price(
+    base_fee: &NiceBigUint,
+    max_priority_fee_per_gas: &NiceBigUint,
+    max_fee_per_gas: &NiceBigUint,
+) -> NiceBigUint {
+    let price = &base_fee.0 + &max_priority_fee_per_gas.0;
+    NiceBigUint(price.min(max_fee_per_gas.0.clone()))
+}
+
+// Typed transactions sign their type followed by the RLP list of all fields except the signature
+fn signing_hash<T: Serialize>(transaction_type: u8, payload: &T) -> ByteArray<32> {
+    let mut message = vec![transaction_type];
+    message.extend(to_bytes(payload).expect("Should be impossible."));
+    keccak256(&message)
+}
+
+#[derive(Serialize)]
+struct Tagged<'a, T> {
+    #[serde(flatten)]
+    annotations: Annotations,
+    #[serde(flatten)]
+    transaction: &'a T,
+}
+
+// The fields which are computed from a transaction for JSON
+#[derive(Serialize)]
+struct Annotations {
+    hash: ByteArray<32>,
+    #[serde(
+        rename = "type",
+        serialize_with = "crate::types::option_str_serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    transaction_type: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<ByteArray<20>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chain_id: Option<NiceBigUint>,
+    #[serde(
+        serialize_with = "crate::types::option_str_serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    y_parity: Option<u8>,
+}
+
+impl<'de> Deserialize<'de> for TypedTransaction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(TypedTransactionVisitor)
+    }
+}
+
+struct TypedTransactionVisitor;
+
+impl<'de> Visitor<'de> for TypedTransactionVisitor {
+    type Value = TypedTransaction;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a legacy transaction list or a typed transaction string")
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        Transaction::deserialize(de::value::SeqAccessDeserializer::new(seq))
+            .map(TypedTransaction::Legacy)
+    }
+
+    fn visit_bytes<E>(self, envelope: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match envelope.split_first() {
+            Some((&AccessListTransaction::TYPE, payload)) => from_bytes(payload)
+                .map(TypedTransaction::AccessList)
+                .map_err(E::custom),
+            Some((&DynamicFeeTransaction::TYPE, payload)) => from_bytes(payload)
+                .map(TypedTransaction::DynamicFee)
+                .map_err(E::custom),
+            Some((&BlobTransaction::TYPE, payload)) => from_bytes(payload)
+                .map(TypedTransaction::Blob)
+                .map_err(E::custom),
+            Some((&SetCodeTransaction::TYPE, payload)) => from_bytes(payload)
+                .map(TypedTransaction::SetCode)
+                .map_err(E::custom),
+            Some((&transaction_type @ 0x00..=0x7f, payload)) => {
+                Ok(TypedTransaction::Unknown(UnknownTransaction {
+                    transaction_type,
+                    payload: ByteVec(payload.to_vec()),
+                }))
+            }
+            _ => Err(E::invalid_value(Unexpected::Bytes(envelope), &self)),
+        }
+    }
+}
+
+/// An EIP-2930 transaction with an access list
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AccessListTransaction {
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub chain_id: u64,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub nonce: u64,
+    pub gas_price: NiceBigUint,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub gas: u64,
+    #[serde(
+        deserialize_with = "deserialize_transaction",
+        serialize_with = "serialize_transaction"
+    )]
+    pub to: To,
+    pub value: NiceBigUint,
+    pub data: ByteVec,
+    pub access_list: NiceVec<AccessListItem>,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub y_parity: u8,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
+    pub r: ByteArray<32>,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
+    pub s: ByteArray<32>,
+}
+
+impl AccessListTransaction {
+    /// The EIP-2718 transaction type
+    pub const TYPE: u8 = 0x01;
+
+    /// The hash which the sender signed
+    pub fn signing_hash(&self) -> ByteArray<32> {
+        signing_hash(
+            Self::TYPE,
+            &(
+                self.chain_id,
+                self.nonce,
+                &self.gas_price,
+                self.gas,
+                Recipient(&self.to),
+                &self.value,
+                &self.data,
+                &self.access_list,
+            ),
+        )
+    }
+
+    /// The address which sent the transaction, recovered from the signature
+    pub fn sender(&self) -> Option<ByteArray<20>> {
+        recover_address(&self.signing_hash(), self.y_parity, &self.r, &self.s)
+    }
+
+    /// The keccak-256 hash of the encoded transaction
+    pub fn hash(&self) -> ByteArray<32> {
+        keccak256(&encode_envelope(Self::TYPE, self).expect("Should be impossible."))
+    }
+}
+
+impl std::fmt::Display for AccessListTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// An EIP-1559 transaction with a priority fee on top of the base fee of the block
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DynamicFeeTransaction {
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub chain_id: u64,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: NiceBigUint,
+    pub max_fee_per_gas: NiceBigUint,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub gas: u64,
+    #[serde(
+        deserialize_with = "deserialize_transaction",
+        serialize_with = "serialize_transaction"
+    )]
+    pub to: To,
+    pub value: NiceBigUint,
+    pub data: ByteVec,
+    pub access_list: NiceVec<AccessListItem>,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub y_parity: u8,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
+    pub r: ByteArray<32>,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
+    pub s: ByteArray<32>,
+}
+
+impl DynamicFeeTransaction {
+    /// The EIP-2718 transaction type
+    pub const TYPE: u8 = 0x02;
+
+    /// The price per gas paid by the sender, which depends on the base fee of the block
+    pub fn effective_gas_price(&self, base_fee: &NiceBigUint) -> NiceBigUint {
+        effective_gas_price(
+            base_fee,
+            &self.max_priority_fee_per_gas,
+            &self.max_fee_per_gas,
+        )
+    }
+
+    /// The hash which the sender signed
+    pub fn signing_hash(&self) -> ByteArray<32> {
+        signing_hash(
+            Self::TYPE,
+            &(
+                self.chain_id,
+                self.nonce,
+                &self.max_priority_fee_per_gas,
+                &self.max_fee_per_gas,
+                self.gas,
+                Recipient(&self.to),
+                &self.value,
+                &self.data,
+                &self.access_list,
+            ),
+        )
+    }
+
+    /// The address which sent the transaction, recovered from the signature
+    pub fn sender(&self) -> Option<ByteArray<20>> {
+        recover_address(&self.signing_hash(), self.y_parity, &self.r, &self.s)
+    }
+
+    /// The keccak-256 hash of the encoded transaction
+    pub fn hash(&self) -> ByteArray<32> {
+        keccak256(&encode_envelope(Self::TYPE, self).expect("Should be impossible."))
+    }
+}
+
+impl std::fmt::Display for DynamicFeeTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// An EIP-4844 transaction which carries blobs for rollups
+///
+/// The blobs themselves are not part of the block, only their versioned hashes are.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BlobTransaction {
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub chain_id: u64,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: NiceBigUint,
+    pub max_fee_per_gas: NiceBigUint,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub gas: u64,
+    pub to: ByteArray<20>,
+    pub value: NiceBigUint,
+    pub data: ByteVec,
+    pub access_list: NiceVec<AccessListItem>,
+    pub max_fee_per_blob_gas: NiceBigUint,
+    pub blob_versioned_hashes: NiceVec<ByteArray<32>>,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub y_parity: u8,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
+    pub r: ByteArray<32>,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
+    pub s: ByteArray<32>,
+}
+
+impl BlobTransaction {
+    /// The EIP-2718 transaction type
+    pub const TYPE: u8 = 0x03;
+    /// The blob gas used by every blob
+    pub const GAS_PER_BLOB: u64 = 131_072;
+
+    /// The price per unit of execution gas, the blob gas is paid for separately
+    pub fn effective_gas_price(&self, base_fee: &NiceBigUint) -> NiceBigUint {
+        effective_gas_price(
+            base_fee,
+            &self.max_priority_fee_per_gas,
+            &self.max_fee_per_gas,
+        )
+    }
+
+    /// The hash which the sender signed
+    pub fn signing_hash(&self) -> ByteArray<32> {
+        signing_hash(
+            Self::TYPE,
+            &(
+                self.chain_id,
+                self.nonce,
+                &self.max_priority_fee_per_gas,
+                &self.max_fee_per_gas,
+                self.gas,
+                &self.to,
+                &self.value,
+                &self.data,
+                &self.access_list,
+                &self.max_fee_per_blob_gas,
+                &self.blob_versioned_hashes,
+            ),
+        )
+    }
+
+    /// The address which sent the transaction, recovered from the signature
+    pub fn sender(&self) -> Option<ByteArray<20>> {
+        recover_address(&self.signing_hash(), self.y_parity, &self.r, &self.s)
+    }
+
+    /// The keccak-256 hash of the encoded transaction
+    pub fn hash(&self) -> ByteArray<32> {
+        keccak256(&encode_envelope(Self::TYPE, self).expect("Should be impossible."))
+    }
+
+    /// The blob gas used by this transaction
+    pub fn blob_gas(&self) -> u64 {
+        self.blob_versioned_hashes.0.len() as u64 * Self::GAS_PER_BLOB
+    }
+
+    /// The fee paid for the blobs, given the blob base fee of the block
+    pub fn blob_fee(&self, blob_base_fee: &NiceBigUint) -> NiceBigUint {
+        NiceBigUint(&blob_base_fee.0 * self.blob_gas())
+    }
+}
+
+impl std::fmt::Display for BlobTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// An EIP-7702 transaction which sets the code of externally owned accounts
+///
+/// Every authority in the authorization list delegates the code of its account to an address.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SetCodeTransaction {
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub chain_id: u64,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: NiceBigUint,
+    pub max_fee_per_gas: NiceBigUint,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub gas: u64,
+    pub to: ByteArray<20>,
+    pub value: NiceBigUint,
+    pub data: ByteVec,
+    pub access_list: NiceVec<AccessListItem>,
+    pub authorization_list: NiceVec<Authorization>,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub y_parity: u8,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
+    pub r: ByteArray<32>,
+    #[serde(
+        deserialize_with = "deserialize_signature",
+        serialize_with = "serialize_signature"
+    )]
+    pub s: ByteArray<32>,
+}
+
+impl SetCodeTransaction {
+    /// The EIP-2718 transaction type
+    pub const TYPE: u8 = 0x04;
+
+    /// The price per gas paid by the sender, like for dynamic fee transactions
+    pub fn effective_gas_price(&self, base_fee: &NiceBigUint) -> NiceBigUint {
+        effective_gas_price(
+            base_fee,
+            &self.max_priority_fee_per_gas,
+            &self.max_fee_per_gas,
+        )
+    }
+
+    /// The hash which the sender signed
+    pub fn signing_hash(&self) -> ByteArray<32> {
+        signing_hash(
+            Self::TYPE,
+            &(
+                self.chain_id,
+                self.nonce,
+                &self.max_priority_fee_per_gas,
+                &self.max_fee_per_gas,
+                self.gas,
+                &self.to,
+                &self.value,
+                &self.data,
+                &self.access_list,
+                &self.authorization_list,
+            ),
+        )
+    }
+
+    /// The address which sent the transaction, recovered from the signature
+    pub fn sender(&self) -> Option<ByteArray<20>> {
+        recover_address(&self.signing_hash(), self.y_parity, &self.r, &self.s)
+    }
+
+    /// The keccak-256 hash of the encoded transaction
+    pub fn hash(&self) -> ByteArray<32> {
+        keccak256(&encode_envelope(Self::TYPE, self).expect("Should be impossible."))
+    }
+}
+
+impl std::fmt::Display for SetCodeTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// A signed delegation of the code of an account to an address
+///
+/// A chain id of 0 makes the authorization valid on every chain.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Authorization {
+    pub chain_id: NiceBigUint,
+    pub address: ByteArray<20>,
+    pub nonce: u64,
+    pub y_parity: u8,
+    #[serde(deserialize_with = "deserialize_signature")]
+    pub r: ByteArray<32>,
+    #[serde(deserialize_with = "deserialize_signature")]
+    pub s: ByteArray<32>,
+}
+
+impl Authorization {
+    /// The prefix of the signed message, which separates it from transactions
+    pub const MAGIC: u8 = 0x05;
+
+    /// The hash which the authority signed
+    pub fn signing_hash(&self) -> ByteArray<32> {
+        signing_hash(Self::MAGIC, &(&self.chain_id, &self.address, self.nonce))
+    }
+
+    /// The account which delegates its code, recovered from the signature
+    ///
+    /// Returns `None` for invalid signatures, which are skipped during execution.
+    pub fn authority(&self) -> Option<ByteArray<20>> {
+        if !is_low_s(&self.s) {
+            return None;
+        }
+        recover_address(&self.signing_hash(), self.y_parity, &self.r, &self.s)
+    }
+}
+
+// The authority is recovered from the signature, so it is only added to JSON
+impl Serialize for Authorization {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let authority = if serializer.is_human_readable() {
+            Some(self.authority())
+        } else {
+            None
+        };
+        AuthorizationView {
+            chain_id: &self.chain_id,
+            address: &self.address,
+            nonce: self.nonce,
+            y_parity: self.y_parity,
+            r: &self.r,
+            s: &self.s,
+            authority,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Serialize)]
+struct AuthorizationView<'a> {
+    chain_id: &'a NiceBigUint,
+    address: &'a ByteArray<20>,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    nonce: u64,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    y_parity: u8,
+    #[serde(serialize_with = "serialize_signature")]
+    r: &'a ByteArray<32>,
+    #[serde(serialize_with = "serialize_signature")]
+    s: &'a ByteArray<32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authority: Option<Option<ByteArray<20>>>,
+}
+
+impl std::fmt::Display for Authorization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// An address and the storage keys a transaction plans to access
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AccessListItem {
+    pub address: ByteArray<20>,
+    pub storage_keys: NiceVec<ByteArray<32>>,
+}
+
+impl std::fmt::Display for AccessListItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// A typed transaction whose type is not supported, the payload is kept as it is
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnknownTransaction {
+    #[serde(rename = "type", serialize_with = "crate::types::str_serialize")]
+    pub transaction_type: u8,
+    pub payload: ByteVec,
+}
+
+impl std::fmt::Display for UnknownTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BlockBody;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_typed_transaction_deserialize() {
+        // A body with the legacy transaction of block 46147 followed by the EIP-2930 access list
+        // transaction with the nonce 9215 of 0xe9c790e8fde820ded558a4771b72eec916c04763 on
+        // mainnet. Both transactions are taken from the chain, the body around them is not.
+        let legacy_input: Vec<u8> = vec![
+            0xf8, 0x67, 0x80, 0x86, 0x2d, 0x79, 0x88, 0x3d, 0x20, 0x00, 0x82, 0x52, 0x08, 0x94,
+            0x5d, 0xf9, 0xb8, 0x79, 0x91, 0x26, 0x2f, 0x6b, 0xa4, 0x71, 0xf0, 0x97, 0x58, 0xcd,
+            0xe1, 0xc0, 0xfc, 0x1d, 0xe7, 0x34, 0x82, 0x7a, 0x69, 0x80, 0x1c, 0xa0, 0x88, 0xff,
+            0x6c, 0xf0, 0xfe, 0xfd, 0x94, 0xdb, 0x46, 0x11, 0x11, 0x49, 0xae, 0x4b, 0xfc, 0x17,
+            0x9e, 0x9b, 0x94, 0x72, 0x1f, 0xff, 0xd8, 0x21, 0xd3, 0x8d, 0x16, 0x46, 0x4b, 0x3f,
+            0x71, 0xd0, 0xa0, 0x45, 0xe0, 0xaf, 0xf8, 0x00, 0x96, 0x1c, 0xfc, 0xe8, 0x05, 0xda,
+            0xef, 0x70, 0x16, 0xb9, 0xb6, 0x75, 0xc1, 0x37, 0xa6, 0xa4, 0x1a, 0x54, 0x8f, 0x7b,
+            0x60, 0xa3, 0x48, 0x4c, 0x06, 0xa3, 0x3a,
+        ];
+        let access_list_input =
+            std::fs::read("./fixtures/transactions/mainnet_access_list_nonce_9215.rlp").unwrap();
+        let body_input = [
+            &[0xf9, 0x01, 0x9a, 0xf9, 0x01, 0x96][..],
+            &legacy_input,
+            &access_list_input,
+            &[0xc0],
+        ]
+        .concat();
+        let body = from_bytes::<BlockBody>(&body_input).unwrap();
+        assert_eq!(body.transactions.0.len(), 2);
+        assert_eq!(
+            body.transactions.0[0],
+            TypedTransaction::Legacy(from_bytes(&legacy_input).unwrap())
+        );
+        assert_eq!(body.uncles, NiceVec(vec![]));
+        assert_eq!(body.withdrawals, None);
+
+        let TypedTransaction::AccessList(ref access_list) = body.transactions.0[1] else {
+            panic!(
+                "Expected an access list transaction, got {}",
+                body.transactions.0[1]
+            );
+        };
+        assert_eq!(body.transactions.0[1].transaction_type(), 1);
+        assert_eq!(access_list.chain_id, 1);
+        assert_eq!(access_list.nonce, 9215);
+        assert_eq!(
+            access_list.gas_price,
+            NiceBigUint(BigUint::from(43_000_000_000_u64))
+        );
+        assert_eq!(access_list.gas, 1_000_000);
+        assert_eq!(
+            access_list.to,
+            To::Address(ByteArray::<20>([
+                0x00, 0x00, 0x00, 0x00, 0x00, 0xa8, 0xfb, 0x09, 0xaf, 0x94, 0x4a, 0xb3, 0xba, 0xf7,
+                0xa9, 0xb3, 0xe1, 0xab, 0x29, 0xd8,
+            ]))
+        );
+        assert_eq!(access_list.value, NiceBigUint(BigUint::from(0_u32)));
+        assert_eq!(access_list.data.0.len(), 118);
+        assert_eq!(access_list.access_list.0.len(), 3);
+        assert!(access_list
+            .access_list
+            .0
+            .iter()
+            .all(|item| item.storage_keys.0.is_empty()));
+        assert_eq!(access_list.y_parity, 1);
+        assert_eq!(to_bytes(&body).unwrap(), body_input);
+
+        let json = serde_json::to_value(&body.transactions.0[1]).unwrap();
+        assert_eq!(json["type"], "1");
+        assert_eq!(
+            json["access_list"][2]["address"],
+            "0x25647e01bd0967c1b9599fa3521939871d1d0888"
+        );
+        assert_eq!(
+            json["access_list"][2]["storage_keys"],
+            serde_json::json!([])
+        );
+
+        // Transaction types without support are kept as they are
+        let unknown_input = [0x82, 0x7f, 0xc0];
+        let unknown = from_bytes::<TypedTransaction>(&unknown_input).unwrap();
+        assert_eq!(
+            unknown,
+            TypedTransaction::Unknown(UnknownTransaction {
+                transaction_type: 0x7f,
+                payload: ByteVec(vec![0xc0]),
+            })
+        );
+        assert_eq!(to_bytes(&unknown).unwrap(), unknown_input);
+
+        // Type bytes from 0x80 onwards are reserved for RLP lists and strings
+        assert!(from_bytes::<TypedTransaction>(&[0x82, 0xc0, 0xc0]).is_err());
+        assert!(from_bytes::<TypedTransaction>(&[0x80]).is_err());
+    }
+
+    #[test]
+    fn test_dynamic_fee_transaction_deserialize() {
+        // The EIP-1559 transaction 0xce4dc6d7...aab31 on mainnet with a priority fee of 1 gwei and
+        // a maximum fee of about 11.25 gwei
+        let transaction_input =
+            std::fs::read("./fixtures/transactions/mainnet_dynamic_fee_ce4dc6d7.rlp").unwrap();
+        let transaction = from_bytes::<TypedTransaction>(&transaction_input).unwrap();
+        let TypedTransaction::DynamicFee(ref dynamic_fee) = transaction else {
+            panic!("Expected a dynamic fee transaction, got {}", transaction);
+        };
+        assert_eq!(dynamic_fee.chain_id, 1);
+        assert_eq!(dynamic_fee.nonce, 2);
+        assert_eq!(dynamic_fee.gas, 39152);
+        assert_eq!(dynamic_fee.data, ByteVec(vec![0x1b, 0x55, 0xba, 0x3a]));
+        assert_eq!(dynamic_fee.access_list, NiceVec(vec![]));
+        assert_eq!(dynamic_fee.y_parity, 0);
+        assert_eq!(to_bytes(&transaction).unwrap(), transaction_input);
+
+        let gwei = |amount: u64| NiceBigUint(BigUint::from(amount * 1_000_000_000));
+        assert_eq!(
+            transaction.effective_gas_price(Some(&gwei(5))),
+            Some(gwei(6))
+        );
+        assert_eq!(
+            transaction.effective_gas_price(Some(&gwei(11))),
+            Some(NiceBigUint(BigUint::from(11_248_607_958_u64)))
+        );
+        assert_eq!(transaction.effective_gas_price(None), None);
+    }
+
+    #[test]
+    fn test_blob_transaction_deserialize() {
+        // The EIP-4844 transaction 0x93fc9daa...85e87 on mainnet with two blobs and a maximum fee
+        // of 1 wei per blob gas
+        let transaction_input =
+            std::fs::read("./fixtures/transactions/mainnet_blob_93fc9daa.rlp").unwrap();
+        let transaction = from_bytes::<TypedTransaction>(&transaction_input).unwrap();
+        let TypedTransaction::Blob(ref blob) = transaction else {
+            panic!("Expected a blob transaction, got {}", transaction);
+        };
+        assert_eq!(blob.chain_id, 1);
+        assert_eq!(blob.nonce, 15435);
+        assert_eq!(blob.gas, 8_000_000);
+        assert_eq!(blob.data.0.len(), 1860);
+        assert_eq!(blob.max_fee_per_blob_gas, NiceBigUint(BigUint::from(1_u32)));
+        assert_eq!(
+            blob.blob_versioned_hashes.0,
+            vec![
+                ByteArray::<32>([
+                    0x01, 0xe5, 0x27, 0x6d, 0x91, 0xac, 0x1d, 0xdb, 0x3b, 0x1c, 0x2d, 0x61, 0x29,
+                    0x52, 0x11, 0x22, 0x00, 0x36, 0xe9, 0xa0, 0x4b, 0xe2, 0x4c, 0x00, 0xf7, 0x69,
+                    0x16, 0xcc, 0x26, 0x59, 0xd0, 0x04,
+                ]),
+                ByteArray::<32>([
+                    0x01, 0x28, 0xeb, 0x58, 0xaf, 0xf0, 0x9f, 0xd3, 0xa7, 0x95, 0x7c, 0xd8, 0x0a,
+                    0xa8, 0x61, 0x86, 0xd5, 0x84, 0x95, 0x69, 0x99, 0x7c, 0xdf, 0xcf, 0xa2, 0x37,
+                    0x72, 0x81, 0x1b, 0x70, 0x6c, 0xc2,
+                ]),
+            ]
+        );
+        assert_eq!(blob.y_parity, 0);
+        assert_eq!(blob.blob_gas(), 262_144);
+        assert_eq!(
+            blob.blob_fee(&NiceBigUint(BigUint::from(2_u32))),
+            NiceBigUint(BigUint::from(524_288_u32))
+        );
+        assert_eq!(to_bytes(&transaction).unwrap(), transaction_input);
+        assert_eq!(serde_json::to_value(&transaction).unwrap()["type"], "3");
+    }
+
+    #[test]
+    fn test_set_code_transaction_deserialize() {
+        // The EIP-7702 transaction 0xadc3f24d...ecad83 with a single authorization on Mekong, a
+        // public testnet of the Pectra hard fork with the chain id 7078815900. Set code
+        // transactions from mainnet are not part of the fixtures yet.
+        let transaction_input =
+            std::fs::read("./fixtures/transactions/mekong_set_code_adc3f24d.rlp").unwrap();
+        let transaction = from_bytes::<TypedTransaction>(&transaction_input).unwrap();
+        let TypedTransaction::SetCode(ref set_code) = transaction else {
+            panic!("Expected a set code transaction, got {}", transaction);
+        };
+        assert_eq!(set_code.chain_id, 7_078_815_900);
+        assert_eq!(set_code.nonce, 26);
+        assert_eq!(set_code.gas, 63660);
+        assert_eq!(set_code.data, ByteVec(vec![]));
+        assert_eq!(set_code.y_parity, 1);
+
+        let authorization = &set_code.authorization_list.0[0];
+        assert_eq!(set_code.authorization_list.0.len(), 1);
+        assert_eq!(
+            authorization.chain_id,
+            NiceBigUint(BigUint::from(7_078_815_900_u64))
+        );
+        assert_eq!(
+            authorization.address,
+            ByteArray::<20>([
+                0x52, 0x9f, 0x77, 0x31, 0x25, 0x64, 0x2b, 0x12, 0xa4, 0x4b, 0xd5, 0x43, 0x00, 0x56,
+                0x50, 0x98, 0x9e, 0xce, 0xaa, 0x2a,
+            ])
+        );
+        assert_eq!(authorization.nonce, 26);
+        assert_eq!(authorization.y_parity, 0);
+
+        // The maximum fee is 8 wei above the priority fee of 235375000 wei
+        assert_eq!(
+            transaction.effective_gas_price(Some(&NiceBigUint(BigUint::from(7_u32)))),
+            Some(NiceBigUint(BigUint::from(235_375_007_u32)))
+        );
+        assert_eq!(
+            transaction.effective_gas_price(Some(&NiceBigUint(BigUint::from(9_u32)))),
+            Some(NiceBigUint(BigUint::from(235_375_008_u32)))
+        );
+        assert_eq!(to_bytes(&transaction).unwrap(), transaction_input);
+        let json = serde_json::to_value(&transaction).unwrap();
+        assert_eq!(json["type"], "4");
+        assert_eq!(json["authorization_list"][0]["nonce"], "26");
+    }
+
+    // Transactions of every type, signed by hand with the private key 0x4646...46 from the
+    // example of EIP-155. The authorization is signed with the private key 0x00...01.
+    fn signed_transactions() -> Vec<Vec<u8>> {
+        vec![
+            vec![
+                0xb8, 0xaa, 0x01, 0xf8, 0xa7, 0x01, 0x80, 0x85, 0x04, 0xa8, 0x17, 0xc8, 0x00, 0x82,
+                0x75, 0x30, 0x94, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+                0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x88, 0x0d, 0xe0, 0xb6, 0xb3,
+                0xa7, 0x64, 0x00, 0x00, 0x80, 0xf8, 0x38, 0xf7, 0x94, 0x35, 0x35, 0x35, 0x35, 0x35,
+                0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+                0x35, 0xe1, 0xa0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xa0, 0x3c, 0x7c, 0x87, 0x50, 0x32,
+                0x80, 0x6b, 0xca, 0x88, 0x53, 0x10, 0xcf, 0x35, 0xe5, 0xa9, 0xf7, 0x77, 0x6e, 0x1e,
+                0xd2, 0x8e, 0xc6, 0x3e, 0xc6, 0xfe, 0xb2, 0x1f, 0x7d, 0x61, 0xf2, 0xc7, 0x94, 0xa0,
+                0x22, 0x8e, 0xc4, 0xe5, 0xea, 0x12, 0xda, 0x4f, 0xce, 0x51, 0x06, 0xec, 0xbf, 0xb8,
+                0xdb, 0x89, 0x04, 0x58, 0xe1, 0x4f, 0x4e, 0x63, 0x60, 0xb3, 0x2e, 0x2c, 0xea, 0x72,
+                0xfa, 0xe6, 0xe0, 0x29,
+            ],
+            vec![
+                0xb8, 0x76, 0x02, 0xf8, 0x73, 0x01, 0x01, 0x84, 0x3b, 0x9a, 0xca, 0x00, 0x85, 0x0b,
+                0xa4, 0x3b, 0x74, 0x00, 0x82, 0x52, 0x08, 0x94, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+                0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+                0x88, 0x0d, 0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00, 0x80, 0xc0, 0x01, 0xa0, 0x4f,
+                0x03, 0xf8, 0xf6, 0x34, 0xdc, 0x4f, 0x0f, 0x70, 0x75, 0x13, 0x9a, 0x3b, 0x0e, 0x16,
+                0xa8, 0x3b, 0x04, 0x87, 0x6a, 0x90, 0xa0, 0x03, 0xe7, 0x22, 0x70, 0x57, 0xa0, 0x60,
+                0x1e, 0x74, 0x07, 0xa0, 0x5f, 0x10, 0xa3, 0xe6, 0x59, 0x43, 0x8a, 0x40, 0xe8, 0x14,
+                0x0e, 0x89, 0xa2, 0xc0, 0x1b, 0x7b, 0x2a, 0x59, 0x0d, 0x77, 0xcc, 0xc5, 0x4e, 0x8d,
+                0x92, 0xd8, 0x3e, 0x9a, 0x7f, 0x89, 0xbe, 0x6a,
+            ],
+            vec![
+                0xb8, 0x91, 0x03, 0xf8, 0x8e, 0x01, 0x02, 0x84, 0x3b, 0x9a, 0xca, 0x00, 0x85, 0x0b,
+                0xa4, 0x3b, 0x74, 0x00, 0x82, 0xc3, 0x50, 0x94, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+                0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+                0x80, 0x80, 0xc0, 0x03, 0xe1, 0xa0, 0x01, 0xc1, 0xdf, 0xa5, 0x73, 0xac, 0xb2, 0x78,
+                0x31, 0x72, 0xc3, 0x5d, 0x98, 0xa5, 0x82, 0xe9, 0x99, 0x38, 0x4a, 0xcb, 0x52, 0x50,
+                0xc3, 0x56, 0x34, 0xb4, 0x61, 0xd8, 0xd7, 0x95, 0xcd, 0xe2, 0x80, 0xa0, 0x93, 0x23,
+                0x09, 0xd8, 0xfb, 0xd4, 0xfa, 0x77, 0x7f, 0x0a, 0x33, 0x0d, 0xf7, 0xdb, 0xfd, 0xa7,
+                0x01, 0x41, 0xa2, 0xdb, 0x13, 0xb0, 0x5b, 0x8f, 0x61, 0xf2, 0x85, 0x66, 0xbe, 0xc1,
+                0xcb, 0xa1, 0xa0, 0x12, 0x41, 0x3c, 0xd1, 0x3c, 0x16, 0x42, 0x0b, 0x19, 0xdd, 0xa6,
+                0xf2, 0x58, 0x8c, 0x1b, 0x19, 0xa3, 0x19, 0x1d, 0xdd, 0x06, 0x2e, 0x8a, 0x86, 0x24,
+                0x67, 0x5a, 0x9b, 0x42, 0xdc, 0x8a, 0x86,
+            ],
+            vec![
+                0xb8, 0xcd, 0x04, 0xf8, 0xca, 0x01, 0x03, 0x84, 0x3b, 0x9a, 0xca, 0x00, 0x85, 0x0b,
+                0xa4, 0x3b, 0x74, 0x00, 0x83, 0x01, 0x86, 0xa0, 0x94, 0x9d, 0x8a, 0x62, 0xf6, 0x56,
+                0xa8, 0xd1, 0x61, 0x5c, 0x12, 0x94, 0xfd, 0x71, 0xe9, 0xcf, 0xb3, 0xe4, 0x85, 0x5a,
+                0x4f, 0x80, 0x80, 0xc0, 0xf8, 0x5c, 0xf8, 0x5a, 0x01, 0x94, 0x35, 0x35, 0x35, 0x35,
+                0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35, 0x35,
+                0x35, 0x35, 0x80, 0x80, 0xa0, 0x13, 0x5b, 0xfd, 0x49, 0xaf, 0x38, 0x6c, 0xbd, 0x8c,
+                0x9f, 0xc2, 0x15, 0x7d, 0x9d, 0xa7, 0x30, 0x69, 0x89, 0x36, 0x28, 0xa5, 0x94, 0x58,
+                0xd3, 0x9f, 0x97, 0x6f, 0x2a, 0x29, 0xe2, 0x86, 0x85, 0xa0, 0x52, 0xea, 0x5b, 0x51,
+                0x48, 0xe5, 0x07, 0x00, 0x5a, 0x39, 0x79, 0x15, 0xf9, 0x0c, 0xdc, 0xfe, 0x13, 0xea,
+                0xb9, 0xbd, 0x52, 0x8b, 0xef, 0x64, 0xe8, 0x0b, 0x1f, 0xad, 0x34, 0xed, 0xfe, 0xa5,
+                0x01, 0xa0, 0xe0, 0x72, 0x5c, 0x11, 0x7d, 0xf6, 0x91, 0xdc, 0xc2, 0x45, 0x84, 0x73,
+                0x62, 0x58, 0xaa, 0x69, 0xc6, 0xaa, 0xbb, 0x6b, 0xdf, 0x14, 0x53, 0x7d, 0xfc, 0x14,
+                0xc3, 0xe5, 0xf9, 0x13, 0xfa, 0xe7, 0xa0, 0x31, 0xbc, 0x8a, 0x11, 0x51, 0x34, 0xc5,
+                0x76, 0xe3, 0x80, 0x58, 0x0d, 0x05, 0xb3, 0x8d, 0x73, 0x1e, 0xc4, 0xe7, 0x40, 0xae,
+                0xdb, 0xa8, 0xde, 0x35, 0x68, 0x63, 0x4d, 0xea, 0x27, 0x4f, 0xda,
+            ],
+        ]
+    }
+
+    #[test]
+    fn test_transaction_sender() {
+        // Transactions from the chain with the senders that block explorers show for them. The
+        // legacy transaction is replay protected by EIP-155. There is no blob or set code
+        // transaction from mainnet with a known sender in the fixtures, so these two come from
+        // Sepolia and the Mekong testnet.
+        for (name, transaction_type, sender) in [
+            (
+                "mainnet_legacy_280cde7c",
+                0,
+                "0xa12e1462d0ced572f396f58b6e2d03894cd7c8a4",
+            ),
+            (
+                "mainnet_access_list_nonce_9215",
+                1,
+                "0xe9c790e8fde820ded558a4771b72eec916c04763",
+            ),
+            (
+                "mainnet_dynamic_fee_ce4dc6d7",
+                2,
+                "0x001e2b7de757ba469a57bf6b23d982458a07efce",
+            ),
+            (
+                "sepolia_blob_9a22ccb0",
+                3,
+                "0xa83c816d4f9b2783761a22ba6fadb0eb0606d7b2",
+            ),
+            (
+                "mekong_set_code_adc3f24d",
+                4,
+                "0x6d2d4e1c2326a069f36f5d6337470dc26adb7156",
+            ),
+        ] {
+            let input = std::fs::read(format!("./fixtures/transactions/{}.rlp", name)).unwrap();
+            let transaction = from_bytes::<TypedTransaction>(&input).unwrap();
+            assert_eq!(transaction.transaction_type(), transaction_type);
+            assert_eq!(
+                transaction.sender().unwrap().to_string(),
+                sender,
+                "{}",
+                name
+            );
+        }
+
+        let sender = "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f";
+        let inputs = signed_transactions();
+        for (input, transaction_type) in inputs.iter().zip(1..) {
+            let transaction = from_bytes::<TypedTransaction>(input).unwrap();
+            assert_eq!(transaction.transaction_type(), transaction_type);
+            assert_eq!(transaction.sender().unwrap().to_string(), sender);
+            let json = serde_json::to_value(&transaction).unwrap();
+            assert_eq!(json["from"], sender);
+            assert_eq!(to_bytes(&transaction).unwrap(), *input);
+        }
+
+        let transaction = from_bytes::<TypedTransaction>(&inputs[3]).unwrap();
+        let TypedTransaction::SetCode(mut set_code) = transaction else {
+            panic!("Expected a set code transaction, got {}", transaction);
+        };
+        let authority = "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+        let authorization = &mut set_code.authorization_list.0[0];
+        assert_eq!(authorization.authority().unwrap().to_string(), authority);
+        assert_eq!(
+            serde_json::to_value(&*authorization).unwrap()["authority"],
+            authority
+        );
+
+        // A different nonce or a high s invalidate the authorization
+        authorization.nonce = 1;
+        assert_ne!(authorization.authority().unwrap().to_string(), authority);
+        authorization.s = ByteArray::<32>([0xff; 32]);
+        assert_eq!(authorization.authority(), None);
+        assert_eq!(
+            serde_json::to_value(&*authorization).unwrap()["authority"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_transaction_hash() {
+        // Transactions from the chain with the hashes that block explorers show for them. There is
+        // no set code transaction from mainnet in the fixtures, so it comes from the Mekong
+        // testnet.
+        for (name, hash) in [
+            (
+                "mainnet_access_list_f98c9f1a",
+                "0xf98c9f1a2f30ee316ea1db18c132ccab6383b8e4933ccf6259ca9d1f27d4a364",
+            ),
+            (
+                "mainnet_dynamic_fee_ce4dc6d7",
+                "0xce4dc6d7a7549a98ee3b071b67e970879ff51b5b95d1c340bacd80fa1e1aab31",
+            ),
+            (
+                "mainnet_blob_93fc9daa",
+                "0x93fc9daaa0726c3292a2e939df60f7e773c6a6a726a61ce43f4a217c64d85e87",
+            ),
+            (
+                "mekong_set_code_adc3f24d",
+                "0xadc3f24d05f05f1065debccb1c4b033eaa35917b69b343d88d9062cdf8ecad83",
+            ),
+        ] {
+            let input = std::fs::read(format!("./fixtures/transactions/{}.rlp", name)).unwrap();
+            let transaction = from_bytes::<TypedTransaction>(&input).unwrap();
+            // The hash covers the envelope, but not the RLP string wrapping it in the body
+            assert_eq!(
+                transaction.encode(),
+                from_bytes::<ByteVec>(&input).unwrap().0
+            );
+            assert_eq!(transaction.hash().to_string(), hash, "{}", name);
+            assert_eq!(serde_json::to_value(&transaction).unwrap()["hash"], hash);
+        }
+
+        let transaction = TypedTransaction::Unknown(UnknownTransaction {
+            transaction_type: 0x7f,
+            payload: ByteVec(vec![0xc0]),
+        });
+        assert_eq!(transaction.encode(), vec![0x7f, 0xc0]);
+        let json = serde_json::to_string(&transaction).unwrap();
+        assert_eq!(json.matches("\"type\"").count(), 1);
+        let json = serde_json::to_value(&transaction).unwrap();
+        assert_eq!(json["hash"], transaction.hash().to_string());
+        assert!(json.get("from").is_none());
+    }
+}
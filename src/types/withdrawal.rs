@@ -0,0 +1,74 @@
+use super::ByteArray;
+use serde::{Deserialize, Serialize};
+
+/// A withdrawal from the beacon chain, added by EIP-4895 in the Shanghai hard fork
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Withdrawal {
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub index: u64,
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub validator_index: u64,
+    pub address: ByteArray<20>,
+    /// The withdrawn amount in Gwei
+    #[serde(serialize_with = "crate::types::str_serialize")]
+    pub amount: u64,
+}
+
+impl std::fmt::Display for Withdrawal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rlp::{from_bytes, to_bytes};
+    use crate::types::{BlockBody, NiceVec};
+
+    #[test]
+    fn test_withdrawal_deserialize() {
+        // A body without transactions and uncles, but with two withdrawals. It is encoded by hand.
+        let body_input: Vec<u8> = vec![
+            0xf8, 0x46, 0xc0, 0xc0, 0xf8, 0x42, 0xe1, 0x82, 0x12, 0x34, 0x82, 0x56, 0x78, 0x94,
+            0x5d, 0xf9, 0xb8, 0x79, 0x91, 0x26, 0x2f, 0x6b, 0xa4, 0x71, 0xf0, 0x97, 0x58, 0xcd,
+            0xe1, 0xc0, 0xfc, 0x1d, 0xe7, 0x34, 0x85, 0x01, 0x00, 0x00, 0x00, 0x00, 0xdf, 0x82,
+            0x12, 0x35, 0x80, 0x94, 0x38, 0x8c, 0x81, 0x8c, 0xa8, 0xb9, 0x25, 0x1b, 0x39, 0x31,
+            0x31, 0xc0, 0x8a, 0x73, 0x6a, 0x67, 0xcc, 0xb1, 0x92, 0x97, 0x85, 0x07, 0x73, 0x59,
+            0x40, 0x00,
+        ];
+        let body = from_bytes::<BlockBody>(&body_input).unwrap();
+
+        let withdrawals_expected = NiceVec(vec![
+            Withdrawal {
+                index: 0x1234,
+                validator_index: 0x5678,
+                address: ByteArray::<20>([
+                    0x5d, 0xf9, 0xb8, 0x79, 0x91, 0x26, 0x2f, 0x6b, 0xa4, 0x71, 0xf0, 0x97, 0x58,
+                    0xcd, 0xe1, 0xc0, 0xfc, 0x1d, 0xe7, 0x34,
+                ]),
+                amount: 0x1_0000_0000,
+            },
+            Withdrawal {
+                index: 0x1235,
+                validator_index: 0,
+                address: ByteArray::<20>([
+                    0x38, 0x8c, 0x81, 0x8c, 0xa8, 0xb9, 0x25, 0x1b, 0x39, 0x31, 0x31, 0xc0, 0x8a,
+                    0x73, 0x6a, 0x67, 0xcc, 0xb1, 0x92, 0x97,
+                ]),
+                amount: 32_000_000_000,
+            },
+        ]);
+        assert_eq!(body.transactions, NiceVec(vec![]));
+        assert_eq!(body.withdrawals, Some(withdrawals_expected));
+        assert_eq!(
+            body.withdrawals_root().unwrap().to_string(),
+            "0x54aecb922cc92edb40280971a81c54e5c14896785144da2b3738399dcbf767d6"
+        );
+        assert_eq!(to_bytes(&body).unwrap(), body_input);
+    }
+}
@@ -0,0 +1,828 @@
+use crate::extract::{Freezer, FreezerError};
+use crate::table::{FreezerTable, ReadBackend};
+use crate::types::{
+    BlockBody, BlockHash, BlockHeader, ByteArray, NiceBigUint, NiceVec, Receipts, TotalDifficulty,
+};
+use num_bigint::BigUint;
+use serde::Serialize;
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::Path;
+
+/// A value stored in the freezer which differs from the value computed from the other data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Mismatch {
+    /// The name of the checked value
+    pub check: &'static str,
+    pub stored: String,
+    pub computed: String,
+}
+
+impl Mismatch {
+    /// Returns a mismatch if the stored and the computed value differ
+    pub fn compare<T: Display + PartialEq>(
+        check: &'static str,
+        stored: &T,
+        computed: &T,
+    ) -> Option<Self> {
+        (stored != computed).then(|| Mismatch {
+            check,
+            stored: stored.to_string(),
+            computed: computed.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// Checks the hash of a header against the `hashes` table and the parent hash of the next header
+pub fn verify_header_hash(
+    header: &BlockHeader,
+    stored_hash: &BlockHash,
+    next_header: Option<&BlockHeader>,
+) -> Vec<Mismatch> {
+    let hash = header.hash();
+    let mut mismatches = vec![];
+    mismatches.extend(Mismatch::compare("hash", &stored_hash.0, &hash));
+    if let Some(next_header) = next_header {
+        mismatches.extend(Mismatch::compare(
+            "next_parent_hash",
+            &next_header.parent_hash,
+            &hash,
+        ));
+    }
+    mismatches
+}
+
+/// Checks the transactions, receipts and withdrawals roots, the uncles hash and the logs bloom of
+/// a header against the block data
+///
+/// The receipts need to be annotated with the types of their transactions.
+pub fn verify_roots(header: &BlockHeader, body: &BlockBody, receipts: &Receipts) -> Vec<Mismatch> {
+    let mut mismatches = vec![];
+    mismatches.extend(Mismatch::compare(
+        "transactions_root",
+        &header.transactions_root,
+        &body.transactions_root(),
+    ));
+    mismatches.extend(Mismatch::compare(
+        "sha3_uncles",
+        &header.sha3_uncles,
+        &body.uncles_hash(),
+    ));
+    mismatches.extend(Mismatch::compare(
+        "receipts_root",
+        &header.receipts_root,
+        &receipts.root(),
+    ));
+    mismatches.extend(Mismatch::compare(
+        "withdrawals_root",
+        &optional(&header.withdrawals_root),
+        &optional(&body.withdrawals_root()),
+    ));
+    mismatches.extend(Mismatch::compare(
+        "logs_bloom",
+        &header.logs_bloom,
+        &receipts.logs_bloom(),
+    ));
+    mismatches
+}
+
+// Blocks before Shanghai have neither a withdrawals root nor withdrawals
+fn optional(root: &Option<ByteArray<32>>) -> String {
+    root.as_ref()
+        .map_or_else(|| String::from("none"), ToString::to_string)
+}
+
+/// The maximum size of the extra data of a header in bytes
+pub const MAX_EXTRA_DATA_SIZE: usize = 32;
+/// The minimum gas limit of a block
+pub const MIN_GAS_LIMIT: u64 = 5000;
+/// The gas limit may change by less than `1 / GAS_LIMIT_BOUND_DIVISOR` of the parent gas limit
+pub const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+// Since London the gas target is half of the gas limit, so the first London block doubles it
+const ELASTICITY_MULTIPLIER: u64 = 2;
+
+/// A consensus rule which is violated by a header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// The name of the checked rule
+    pub check: &'static str,
+    /// The value of the header
+    pub value: String,
+    /// The values which are allowed by the rule
+    pub expected: String,
+}
+
+impl Violation {
+    fn new(check: &'static str, value: impl Display, expected: impl Display) -> Self {
+        Violation {
+            check,
+            value: value.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            &serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// Checks the consensus rules of a header which do not need the state
+///
+/// The number is checked against the position of the header in the freezer. The rules which
+/// relate a header to its parent are only checked if the parent is given, which is not the case
+/// for the genesis block.
+pub fn verify_header_rules(
+    block_number: u64,
+    header: &BlockHeader,
+    parent: Option<&BlockHeader>,
+) -> Vec<Violation> {
+    let mut violations = vec![];
+    if header.number.value() != &BigUint::from(block_number) {
+        violations.push(Violation::new("number", &header.number, block_number));
+    }
+    let gas_limit = header.gas_limit.value();
+    if header.gas_used.value() > gas_limit {
+        violations.push(Violation::new(
+            "gas_used",
+            &header.gas_used,
+            format!("<= {}", gas_limit),
+        ));
+    }
+    if gas_limit < &BigUint::from(MIN_GAS_LIMIT) {
+        violations.push(Violation::new(
+            "gas_limit",
+            gas_limit,
+            format!(">= {}", MIN_GAS_LIMIT),
+        ));
+    }
+    if header.extra_data.0.len() > MAX_EXTRA_DATA_SIZE {
+        violations.push(Violation::new(
+            "extra_data",
+            format!("{} bytes", header.extra_data.0.len()),
+            format!("<= {} bytes", MAX_EXTRA_DATA_SIZE),
+        ));
+    }
+
+    let parent = match parent {
+        Some(parent) => parent,
+        None => return violations,
+    };
+    let parent_hash = parent.hash();
+    if header.parent_hash != parent_hash {
+        violations.push(Violation::new(
+            "parent_hash",
+            &header.parent_hash,
+            parent_hash,
+        ));
+    }
+    if header.time_stamp <= parent.time_stamp {
+        violations.push(Violation::new(
+            "time_stamp",
+            header.time_stamp,
+            format!("> {}", parent.time_stamp),
+        ));
+    }
+    let mut parent_gas_limit = parent.gas_limit.value().clone();
+    if header.base_fee_per_gas.is_some() && parent.base_fee_per_gas.is_none() {
+        parent_gas_limit *= ELASTICITY_MULTIPLIER;
+    }
+    let bound = &parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR;
+    let difference = if gas_limit > &parent_gas_limit {
+        gas_limit - &parent_gas_limit
+    } else {
+        &parent_gas_limit - gas_limit
+    };
+    if difference >= bound {
+        // The bound is only zero if the parent violates the minimum gas limit
+        let allowed = if bound > BigUint::default() {
+            &bound - 1_u8
+        } else {
+            bound
+        };
+        violations.push(Violation::new(
+            "gas_limit_change",
+            gas_limit,
+            format!(
+                "{} to {}",
+                &parent_gas_limit - &allowed,
+                &parent_gas_limit + &allowed
+            ),
+        ));
+    }
+    violations
+}
+
+/// Checks the difficulty of a header and its total difficulty from the `diffs` table
+///
+/// The difficulty is calculated with the rules of mainnet, which include a difficulty of zero
+/// since the merge, so the total difficulty stays the same from then on. Without a parent only
+/// the genesis block is checked, whose total difficulty is its difficulty.
+pub fn verify_difficulty(
+    header: &BlockHeader,
+    total_difficulty: &TotalDifficulty,
+    parent: Option<(&BlockHeader, &TotalDifficulty)>,
+) -> Vec<Mismatch> {
+    let mut mismatches = vec![];
+    let parent_total_difficulty = match parent {
+        Some((parent, parent_total_difficulty)) => {
+            mismatches.extend(Mismatch::compare(
+                "difficulty",
+                &header.difficulty,
+                &header.expected_difficulty(parent),
+            ));
+            parent_total_difficulty.0.value().clone()
+        }
+        None if header.number.value() == &BigUint::default() => BigUint::default(),
+        None => return mismatches,
+    };
+    let computed = TotalDifficulty(NiceBigUint::from(
+        parent_total_difficulty + header.difficulty.value(),
+    ));
+    mismatches.extend(Mismatch::compare(
+        "total_difficulty",
+        total_difficulty,
+        &computed,
+    ));
+    mismatches
+}
+
+/// Verifies the blocks of a freezer by recomputing values from the stored data
+///
+/// Blocks are expected to be verified in ascending order, the next header is then only read once.
+#[derive(Debug)]
+pub struct Verifier {
+    headers: FreezerTable,
+    hashes: FreezerTable,
+    bodies: FreezerTable,
+    receipts: FreezerTable,
+    next_header: Option<(u64, BlockHeader)>,
+}
+
+impl Verifier {
+    /// Opens the tables of the freezer which are needed for verification
+    pub fn open(ancient_folder: &Path) -> Result<Self, FreezerError> {
+        Self::open_with_backend(ancient_folder, ReadBackend::default())
+    }
+
+    /// Opens the tables of the freezer with the given read backend
+    pub fn open_with_backend(
+        ancient_folder: &Path,
+        backend: ReadBackend,
+    ) -> Result<Self, FreezerError> {
+        let open = |freezer| FreezerTable::open_with_backend(ancient_folder, freezer, backend);
+        Ok(Verifier {
+            headers: open(Freezer::Headers)?,
+            hashes: open(Freezer::Hashes)?,
+            bodies: open(Freezer::Bodies)?,
+            receipts: open(Freezer::Receipts)?,
+            next_header: None,
+        })
+    }
+
+    /// Verifies a single block and returns all mismatches
+    ///
+    /// The hash of the header is checked against the `hashes` table and the next header, which
+    /// is only checked if it is already part of the freezer. The roots, the uncles hash and the
+    /// logs bloom of the header are recomputed from the `bodies` and `receipts` tables.
+    pub fn verify(&mut self, block_number: u64) -> Result<NiceVec<Mismatch>, FreezerError> {
+        let header = match self.next_header.take() {
+            Some((number, header)) if number == block_number => header,
+            _ => self.headers.get(block_number)?,
+        };
+        let stored_hash: BlockHash = self.hashes.get(block_number)?;
+        if block_number + 1 < self.headers.len() {
+            let next_header = self.headers.get(block_number + 1)?;
+            self.next_header = Some((block_number + 1, next_header));
+        }
+        let next_header = self.next_header.as_ref().map(|(_, header)| header);
+        let mut mismatches = verify_header_hash(&header, &stored_hash, next_header);
+
+        let body: BlockBody = self.bodies.get(block_number)?;
+        let mut receipts: Receipts = self.receipts.get(block_number)?;
+        receipts.set_transaction_types(&body.transactions.0);
+        mismatches.extend(verify_roots(&header, &body, &receipts));
+        Ok(NiceVec(mismatches))
+    }
+
+    /// Returns an iterator over the verification results of a range of blocks
+    ///
+    /// The iterator yields the block number together with its mismatches, which are empty for
+    /// valid blocks. It stops after the first error.
+    pub fn iter_range(&mut self, range: Range<u64>) -> VerifyIter<'_> {
+        VerifyIter {
+            verifier: self,
+            range,
+        }
+    }
+}
+
+/// Iterator over the verification results of a [`Verifier`]
+#[derive(Debug)]
+pub struct VerifyIter<'a> {
+    verifier: &'a mut Verifier,
+    range: Range<u64>,
+}
+
+impl<'a> Iterator for VerifyIter<'a> {
+    type Item = Result<(u64, NiceVec<Mismatch>), FreezerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block_number = self.range.next()?;
+        let mismatches = self.verifier.verify(block_number);
+        if mismatches.is_err() {
+            self.range = self.range.end..self.range.end;
+        }
+        Some(mismatches.map(|mismatches| (block_number, mismatches)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+/// Verifies the consensus rules of a range of headers, see [`verify_header_rules`]
+///
+/// Only the `headers` table is needed. Headers are expected to be verified in ascending order,
+/// every header is then only read once.
+#[derive(Debug)]
+pub struct HeaderVerifier {
+    headers: FreezerTable,
+    parent: Option<(u64, BlockHeader)>,
+}
+
+impl HeaderVerifier {
+    /// Opens the `headers` table of the freezer
+    pub fn open(ancient_folder: &Path) -> Result<Self, FreezerError> {
+        Self::open_with_backend(ancient_folder, ReadBackend::default())
+    }
+
+    /// Opens the `headers` table of the freezer with the given read backend
+    pub fn open_with_backend(
+        ancient_folder: &Path,
+        backend: ReadBackend,
+    ) -> Result<Self, FreezerError> {
+        Ok(HeaderVerifier {
+            headers: FreezerTable::open_with_backend(ancient_folder, Freezer::Headers, backend)?,
+            parent: None,
+        })
+    }
+
+    /// Verifies a single header against its parent and returns all violations
+    ///
+    /// The parent is read from the freezer, so the first header of a pruned freezer is only
+    /// checked on its own.
+    pub fn verify(&mut self, block_number: u64) -> Result<NiceVec<Violation>, FreezerError> {
+        let parent = match (self.parent.take(), block_number.checked_sub(1)) {
+            (Some((number, parent)), Some(parent_number)) if number == parent_number => {
+                Some(parent)
+            }
+            (_, Some(parent_number)) if parent_number >= self.headers.tail().hidden => {
+                Some(self.headers.get(parent_number)?)
+            }
+            _ => None,
+        };
+        let header: BlockHeader = self.headers.get(block_number)?;
+        let violations = verify_header_rules(block_number, &header, parent.as_ref());
+        self.parent = Some((block_number, header));
+        Ok(NiceVec(violations))
+    }
+
+    /// Returns an iterator over the verification results of a range of headers
+    ///
+    /// The iterator yields the block number together with its violations, which are empty for
+    /// valid headers. It stops after the first error.
+    pub fn iter_range(&mut self, range: Range<u64>) -> HeaderVerifyIter<'_> {
+        HeaderVerifyIter {
+            verifier: self,
+            range,
+        }
+    }
+}
+
+/// Iterator over the verification results of a [`HeaderVerifier`]
+#[derive(Debug)]
+pub struct HeaderVerifyIter<'a> {
+    verifier: &'a mut HeaderVerifier,
+    range: Range<u64>,
+}
+
+impl<'a> Iterator for HeaderVerifyIter<'a> {
+    type Item = Result<(u64, NiceVec<Violation>), FreezerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block_number = self.range.next()?;
+        let violations = self.verifier.verify(block_number);
+        if violations.is_err() {
+            self.range = self.range.end..self.range.end;
+        }
+        Some(violations.map(|violations| (block_number, violations)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+/// Verifies the difficulties and total difficulties of a range of blocks, see
+/// [`verify_difficulty`]
+///
+/// Only the `headers` and `diffs` tables are needed. Blocks are expected to be verified in
+/// ascending order, every block is then only read once.
+#[derive(Debug)]
+pub struct DifficultyVerifier {
+    headers: FreezerTable,
+    difficulties: FreezerTable,
+    parent: Option<(u64, BlockHeader, TotalDifficulty)>,
+}
+
+impl DifficultyVerifier {
+    /// Opens the `headers` and `diffs` tables of the freezer
+    pub fn open(ancient_folder: &Path) -> Result<Self, FreezerError> {
+        Self::open_with_backend(ancient_folder, ReadBackend::default())
+    }
+
+    /// Opens the `headers` and `diffs` tables of the freezer with the given read backend
+    pub fn open_with_backend(
+        ancient_folder: &Path,
+        backend: ReadBackend,
+    ) -> Result<Self, FreezerError> {
+        let open = |freezer| FreezerTable::open_with_backend(ancient_folder, freezer, backend);
+        Ok(DifficultyVerifier {
+            headers: open(Freezer::Headers)?,
+            difficulties: open(Freezer::Difficulty)?,
+            parent: None,
+        })
+    }
+
+    /// Verifies a single block against its parent and returns all mismatches
+    ///
+    /// The parent is read from the freezer, so the first block of a pruned freezer is skipped.
+    pub fn verify(&mut self, block_number: u64) -> Result<NiceVec<Mismatch>, FreezerError> {
+        let first_available = self
+            .headers
+            .tail()
+            .hidden
+            .max(self.difficulties.tail().hidden);
+        let parent = match (self.parent.take(), block_number.checked_sub(1)) {
+            (Some((number, header, total_difficulty)), Some(parent_number))
+                if number == parent_number =>
+            {
+                Some((header, total_difficulty))
+            }
+            (_, Some(parent_number)) if parent_number >= first_available => Some((
+                self.headers.get(parent_number)?,
+                self.difficulties.get(parent_number)?,
+            )),
+            _ => None,
+        };
+        let header: BlockHeader = self.headers.get(block_number)?;
+        let total_difficulty: TotalDifficulty = self.difficulties.get(block_number)?;
+        let mismatches = verify_difficulty(
+            &header,
+            &total_difficulty,
+            parent
+                .as_ref()
+                .map(|(header, total_difficulty)| (header, total_difficulty)),
+        );
+        self.parent = Some((block_number, header, total_difficulty));
+        Ok(NiceVec(mismatches))
+    }
+
+    /// Returns an iterator over the verification results of a range of blocks
+    ///
+    /// The iterator yields the block number together with its mismatches, which are empty for
+    /// valid blocks. It stops after the first error.
+    pub fn iter_range(&mut self, range: Range<u64>) -> DifficultyVerifyIter<'_> {
+        DifficultyVerifyIter {
+            verifier: self,
+            range,
+        }
+    }
+}
+
+/// Iterator over the verification results of a [`DifficultyVerifier`]
+#[derive(Debug)]
+pub struct DifficultyVerifyIter<'a> {
+    verifier: &'a mut DifficultyVerifier,
+    range: Range<u64>,
+}
+
+impl<'a> Iterator for DifficultyVerifyIter<'a> {
+    type Item = Result<(u64, NiceVec<Mismatch>), FreezerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block_number = self.range.next()?;
+        let mismatches = self.verifier.verify(block_number);
+        if mismatches.is_err() {
+            self.range = self.range.end..self.range.end;
+        }
+        Some(mismatches.map(|mismatches| (block_number, mismatches)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Bloom, ByteVec};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_verify_fixture() {
+        let mut verifier = Verifier::open(&PathBuf::from("./fixtures/full")).unwrap();
+        let results = verifier
+            .iter_range(0..3)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|(_, mismatches)| mismatches.0.is_empty()));
+        assert!(matches!(
+            verifier.verify(3),
+            Err(FreezerError::OutOfBounds(3, 3))
+        ));
+    }
+
+    #[test]
+    fn test_verify_header_hash() {
+        let mut headers =
+            FreezerTable::open(&PathBuf::from("./fixtures/headers"), Freezer::Headers).unwrap();
+        let header: BlockHeader = headers.get(1).unwrap();
+        let next_header: BlockHeader = headers.get(2).unwrap();
+        let stored_hash = BlockHash(header.hash());
+        assert!(verify_header_hash(&header, &stored_hash, Some(&next_header)).is_empty());
+
+        // A corrupted header matches neither the stored hash nor the next header
+        let corrupted = BlockHeader {
+            extra_data: ByteVec(vec![]),
+            ..header
+        };
+        let mismatches = verify_header_hash(&corrupted, &stored_hash, Some(&next_header));
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].check, "hash");
+        assert_eq!(
+            mismatches[0].stored,
+            "0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6"
+        );
+        assert_eq!(mismatches[0].computed, corrupted.hash().to_string());
+        assert_eq!(mismatches[1].check, "next_parent_hash");
+        assert_eq!(mismatches[1].stored, mismatches[0].stored);
+    }
+
+    #[test]
+    fn test_verify_roots() {
+        let ancient_folder = PathBuf::from("./fixtures/full");
+        let header: BlockHeader = FreezerTable::open(&ancient_folder, Freezer::Headers)
+            .unwrap()
+            .get(1)
+            .unwrap();
+        let body: BlockBody = FreezerTable::open(&ancient_folder, Freezer::Bodies)
+            .unwrap()
+            .get(1)
+            .unwrap();
+        let receipts: Receipts = FreezerTable::open(&ancient_folder, Freezer::Receipts)
+            .unwrap()
+            .get(1)
+            .unwrap();
+        assert!(verify_roots(&header, &body, &receipts).is_empty());
+
+        // The body of block 46147 contains a transaction, the receipts of block 1 do not
+        let body: BlockBody =
+            FreezerTable::open(&PathBuf::from("./fixtures/bodies"), Freezer::Bodies)
+                .unwrap()
+                .get(46147)
+                .unwrap();
+        let mismatches = verify_roots(&header, &body, &receipts);
+        assert_eq!(
+            mismatches,
+            vec![Mismatch {
+                check: "transactions_root",
+                stored: header.transactions_root.to_string(),
+                computed: String::from(
+                    "0x4513310fcb9f6f616972a3b948dc5d547f280849a87ebb5af0191f98b87be598"
+                ),
+            }]
+        );
+
+        // Withdrawals without a withdrawals root in the header are reported as well
+        let body = BlockBody {
+            withdrawals: Some(NiceVec(vec![])),
+            ..body
+        };
+        let mismatches = verify_roots(&header, &body, &receipts);
+        assert_eq!(mismatches[1].check, "withdrawals_root");
+        assert_eq!(mismatches[1].stored, "none");
+
+        // A header bloom which is not backed by any log is reported
+        let mut header = header;
+        header
+            .logs_bloom
+            .accrue_log(&ByteArray::<20>([0x11; 20]), &[]);
+        let mismatches = verify_roots(&header, &body, &receipts);
+        assert_eq!(mismatches[2].check, "logs_bloom");
+        assert_eq!(mismatches[2].computed, Bloom::default().to_string());
+
+        // The body of block 3 contains an uncle, which is committed to by the header of block 3
+        let body: BlockBody =
+            FreezerTable::open(&PathBuf::from("./fixtures/bodies"), Freezer::Bodies)
+                .unwrap()
+                .get(3)
+                .unwrap();
+        let header_3: BlockHeader =
+            FreezerTable::open(&PathBuf::from("./fixtures/headers"), Freezer::Headers)
+                .unwrap()
+                .get(3)
+                .unwrap();
+        let mismatches = verify_roots(&header, &body, &receipts);
+        assert_eq!(mismatches[0].check, "sha3_uncles");
+        assert_eq!(mismatches[0].computed, header_3.sha3_uncles.to_string());
+    }
+
+    #[test]
+    fn test_header_verifier_fixture() {
+        // The last header of the fixture is truncated
+        let mut verifier = HeaderVerifier::open(&PathBuf::from("./fixtures/headers")).unwrap();
+        let results = verifier
+            .iter_range(0..99)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(results.len(), 99);
+        assert!(results
+            .iter()
+            .all(|(_, violations)| violations.0.is_empty()));
+
+        // Starting in the middle reads the parent from the freezer
+        assert!(verifier.verify(50).unwrap().0.is_empty());
+        assert!(verifier.verify(99).is_err());
+
+        // The parent of the first available header of a pruned freezer is hidden
+        let mut verifier = HeaderVerifier::open(&PathBuf::from("./fixtures/pruned")).unwrap();
+        assert!(verifier.verify(8).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn test_verify_header_rules() {
+        let mut headers =
+            FreezerTable::open(&PathBuf::from("./fixtures/headers"), Freezer::Headers).unwrap();
+        let parent: BlockHeader = headers.get(1).unwrap();
+        let header: BlockHeader = headers.get(2).unwrap();
+        assert!(verify_header_rules(2, &header, Some(&parent)).is_empty());
+        assert!(verify_header_rules(1, &parent, None).is_empty());
+
+        let checks = |violations: Vec<Violation>| {
+            violations
+                .iter()
+                .map(|violation| violation.check)
+                .collect::<Vec<_>>()
+        };
+        let big = |value: u64| NiceBigUint::from(BigUint::from(value));
+
+        // The header of block 2 is valid, but not at the position of block 3
+        assert_eq!(
+            verify_header_rules(3, &header, Some(&parent)),
+            vec![Violation {
+                check: "number",
+                value: String::from("2"),
+                expected: String::from("3"),
+            }]
+        );
+
+        // Every checked field except the parent hash violates a rule
+        let corrupted = BlockHeader {
+            gas_used: big(6000),
+            gas_limit: big(4000),
+            extra_data: ByteVec(vec![0; 33]),
+            time_stamp: parent.time_stamp,
+            ..header.clone()
+        };
+        assert_eq!(
+            checks(verify_header_rules(2, &corrupted, Some(&parent))),
+            vec![
+                "gas_used",
+                "gas_limit",
+                "extra_data",
+                "time_stamp",
+                "gas_limit_change"
+            ]
+        );
+        // Corrupting the parent changes its hash
+        let parent_corrupted = BlockHeader {
+            extra_data: ByteVec(vec![]),
+            ..parent.clone()
+        };
+        assert_eq!(
+            checks(verify_header_rules(2, &header, Some(&parent_corrupted))),
+            vec!["parent_hash"]
+        );
+
+        // The gas limit of block 1 is 5000, so it may change by at most 3
+        let violations = verify_header_rules(
+            2,
+            &BlockHeader {
+                gas_limit: big(5004),
+                ..header.clone()
+            },
+            Some(&parent),
+        );
+        assert_eq!(violations[0].expected, "4997 to 5003");
+        assert!(verify_header_rules(
+            2,
+            &BlockHeader {
+                gas_limit: big(5003),
+                ..header.clone()
+            },
+            Some(&parent),
+        )
+        .is_empty());
+
+        // The first London block may double the gas limit of its parent
+        let london = BlockHeader {
+            gas_limit: big(10000),
+            base_fee_per_gas: Some(big(1_000_000_000)),
+            ..header
+        };
+        assert!(verify_header_rules(2, &london, Some(&parent)).is_empty());
+    }
+
+    #[test]
+    fn test_difficulty_verifier_fixture() {
+        let mut verifier = DifficultyVerifier::open(&PathBuf::from("./fixtures/full")).unwrap();
+        let results = verifier
+            .iter_range(0..3)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|(_, mismatches)| mismatches.0.is_empty()));
+        assert!(verifier.verify(2).unwrap().0.is_empty());
+        assert!(matches!(
+            verifier.verify(3),
+            Err(FreezerError::OutOfBounds(3, 3))
+        ));
+    }
+
+    #[test]
+    fn test_verify_difficulty() {
+        let ancient_folder = PathBuf::from("./fixtures/full");
+        let mut headers = FreezerTable::open(&ancient_folder, Freezer::Headers).unwrap();
+        let mut difficulties = FreezerTable::open(&ancient_folder, Freezer::Difficulty).unwrap();
+        let genesis: BlockHeader = headers.get(0).unwrap();
+        let genesis_total_difficulty: TotalDifficulty = difficulties.get(0).unwrap();
+        let header: BlockHeader = headers.get(1).unwrap();
+        let total_difficulty: TotalDifficulty = difficulties.get(1).unwrap();
+        assert!(verify_difficulty(&genesis, &genesis_total_difficulty, None).is_empty());
+        let parent = Some((&genesis, &genesis_total_difficulty));
+        assert!(verify_difficulty(&header, &total_difficulty, parent).is_empty());
+
+        // Without a parent, only the genesis block is checked
+        assert!(verify_difficulty(&header, &genesis_total_difficulty, None).is_empty());
+        assert_eq!(
+            verify_difficulty(&genesis, &total_difficulty, None)[0].check,
+            "total_difficulty"
+        );
+
+        // The stored difficulty does not follow the rules, but it adds up to the total difficulty
+        let corrupted = BlockHeader {
+            time_stamp: genesis.time_stamp + 12,
+            ..header.clone()
+        };
+        let mismatches = verify_difficulty(&corrupted, &total_difficulty, parent);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].check, "difficulty");
+        assert_eq!(mismatches[0].stored, "17171480576");
+        assert_eq!(mismatches[0].computed, "17188257792");
+
+        // Since the merge, blocks have no difficulty and the total difficulty stays the same
+        let merge = BlockHeader {
+            number: NiceBigUint::from(BigUint::from(15_537_394_u64)),
+            difficulty: NiceBigUint::from(BigUint::default()),
+            ..header
+        };
+        let parent = Some((&genesis, &total_difficulty));
+        assert!(verify_difficulty(&merge, &total_difficulty, parent).is_empty());
+        let mismatches = verify_difficulty(&merge, &genesis_total_difficulty, parent);
+        assert_eq!(mismatches[0].check, "total_difficulty");
+        assert_eq!(mismatches[0].computed, total_difficulty.to_string());
+    }
+}